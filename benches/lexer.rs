@@ -0,0 +1,67 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use metal::lexer::lexer::Lexer;
+
+// a bit of everything the lexer sees in a bundle, without regexp literals,
+// which need the parser to pick the goal symbol
+const SNIPPET: &str = r#"
+/**
+ * a block comment before a function
+ */
+function render(props, children) {
+    // a line comment
+    const { title = "untitled", items } = props;
+    let total = 0x1F + 1_000_000 + 3.14e-2 + 12n;
+    for (let i = 0; i < items.length; i++) {
+        total += items[i].price ?? 0;
+        if (total >>> 2 !== 0 && !items[i].hidden) {
+            children.push(`<li>${items[i].name}: ${total}</li>`);
+        }
+    }
+    const message = 'escaped \'quote\' and A and \x42';
+    return title.length > 0 ? { title, total, message } : null;
+}
+var ünïcödé = "non-ascii identifiers and strings: 日本語";
+"#;
+
+fn source(copies: usize) -> String {
+    SNIPPET.repeat(copies)
+}
+
+fn count_tokens(src: &str) -> u64 {
+    let mut count = 0;
+    for t in Lexer::new(src) {
+        t.expect("the benchmark source lexes");
+        count += 1;
+    }
+    count
+}
+
+fn tokens_per_second(c: &mut Criterion) {
+    let mut group = c.benchmark_group("tokens");
+    for copies in [10, 1000] {
+        let src = source(copies);
+        group.throughput(Throughput::Elements(count_tokens(&src)));
+        group.bench_with_input(BenchmarkId::from_parameter(src.len()), &src, |b, src| {
+            b.iter(|| Lexer::new(src).filter_map(Result::ok).count())
+        });
+    }
+    group.finish();
+}
+
+fn bytes_per_second(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bytes");
+    let src = source(1000);
+    group.throughput(Throughput::Bytes(src.len() as u64));
+    group.bench_function("mixed", |b| {
+        b.iter(|| Lexer::new(&src).filter_map(Result::ok).count())
+    });
+    let ascii: String = src.chars().filter(|c| c.is_ascii()).collect();
+    group.throughput(Throughput::Bytes(ascii.len() as u64));
+    group.bench_function("ascii", |b| {
+        b.iter(|| Lexer::new(&ascii).filter_map(Result::ok).count())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, tokens_per_second, bytes_per_second);
+criterion_main!(benches);
@@ -1,37 +1,104 @@
-use super::variable::Identifier;
+use num_bigint::BigInt;
+use crate::atom::Atom;
+use crate::js_string::JsString;
+use super::variable::{Identifier, Pattern};
+use super::function::Function;
 #[derive(Debug)]
 pub enum Expression {
     Literal(Literal),
     Bin(BinaryExp),
+    Logical(LogicalExpr),
     Unary(UnaryExpr),
-    Identifier(Identifier)
+    Update(UpdateExpr),
+    Assign(AssignExpr),
+    Conditional(ConditionalExpr),
+    Sequence(SequenceExpr),
+    Identifier(Identifier),
+    This,
+    // only as `super.x` or `super[x]` in a method
+    Super,
+    // `new.target`
+    NewTarget,
+    Member(MemberExpr),
+    Call(CallExpr),
+    New(NewExpr),
+    // a member access or call chain containing `?.`, the whole chain is skipped
+    // when an optional part is nullish
+    Chain(ChainExpr),
+    Template(TemplateLiteral),
+    TaggedTemplate(TaggedTemplateExpr),
+    // `...x` in an argument list
+    Spread(SpreadElement),
+    Array(ArrayExpr),
+    Object(ObjectExpr),
+    // function expressions and arrow functions
+    Function(Box<Function>),
+    Yield(YieldExpr),
+    Await(AwaitExpr),
+    // kept so the parser can tell `(a ?? b) || c` from `a ?? b || c`,
+    // and `(-a) ** b` from `-a ** b`
+    Paren(Box<Expression>)
+}
+
+impl Expression {
+    // the expression inside any number of parentheses
+    pub fn unparenthesized(&self) -> &Expression {
+        match self {
+            Self::Paren(e) => e.unparenthesized(),
+            e => e
+        }
+    }
+    // an identifier or a non-optional member access, the targets of `++`, `--`
+    // and compound assignment like `+=`
+    pub fn is_simple_target(&self) -> bool {
+        matches!(self.unparenthesized(), Self::Identifier(_) | Self::Member(_))
+    }
+}
+
+// a property name in an object literal or pattern, `a`, `"a"`, `1` or `[a]`.
+// a string key is interned, a Literal only when it has a lone surrogate
+#[derive(Debug)]
+pub enum PropertyKey {
+    Identifier(Identifier),
+    String(Atom),
+    Literal(Literal),
+    Computed(Box<Expression>)
 }
 
 #[derive(Debug)]
 pub enum Literal {
     String(Str),
     Number(Number),
+    BigInt(BigIntLiteral),
+    RegExp(RegExpLiteral),
     Null,
     Undefined,
     Boolean(Boolean)
 }
 #[derive(Debug)]
-struct Str {
-    value: String
+pub struct Str {
+    pub value: JsString
+}
+#[derive(Debug)]
+pub struct Number {
+    pub value: f64
 }
 #[derive(Debug)]
-struct Number {
-    value: f64
+pub struct BigIntLiteral {
+    pub value: BigInt
 }
-struct Null {
+#[derive(Debug)]
+pub struct RegExpLiteral {
+    pub pattern: String,
+    pub flags: String
 }
 #[derive(Debug)]
-struct Boolean {
-    value: bool
+pub struct Boolean {
+    pub value: bool
 }
-impl From<String> for Literal {
+impl From<JsString> for Literal {
     #[inline]
-    fn from(st: String) -> Self {
+    fn from(st: JsString) -> Self {
         Self::String(Str {
             value: st
         })
@@ -43,6 +110,12 @@ impl From<f64> for Literal {
         Self::Number(Number { value: n })
     }
 }
+impl From<BigInt> for Literal {
+    #[inline]
+    fn from(n: BigInt) -> Self {
+        Self::BigInt(BigIntLiteral { value: n })
+    }
+}
 impl From<bool> for Literal {
     #[inline]
     fn from(n: bool) -> Self {
@@ -58,21 +131,74 @@ impl From<Literal> for Expression {
 }
 
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum BinaryOpt {
     Add,
     Sub,
     Div,
     Mul,
+    Mod,
+    Exp,
     Lt,
-    Gt
+    Gt,
+    Lte,
+    Gte,
+    Eq,
+    Ne,
+    EqStrict,
+    NeStrict,
+    Shl,
+    Sar,
+    Shr,
+    BitAnd,
+    BitOr,
+    BitXor,
+    In,
+    InstanceOf
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LogicalOp {
+    And,
+    Or,
+    Nullish
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum UnaryOp {
     Add,
     Sub,
-    Bang
+    Bang,
+    BitNot,
+    Typeof,
+    Void,
+    Delete
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UpdateOp {
+    Inc,
+    Dec
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AssignOp {
+    Assign,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Exp,
+    Shl,
+    Sar,
+    Shr,
+    BitAnd,
+    BitOr,
+    BitXor,
+    And,
+    Or,
+    Nullish
 }
 
 #[derive(Debug)]
@@ -81,8 +207,125 @@ pub struct BinaryExp {
     pub left: Box<Expression>,
     pub right: Box<Expression>
 }
+// `&&`, `||` and `??` short-circuit, so they are not plain binary expressions
+#[derive(Debug)]
+pub struct LogicalExpr {
+    pub operator: LogicalOp,
+    pub left: Box<Expression>,
+    pub right: Box<Expression>
+}
 #[derive(Debug)]
 pub struct UnaryExpr {
     pub operator: UnaryOp,
     pub argument: Box<Expression>
-}
\ No newline at end of file
+}
+#[derive(Debug)]
+pub struct UpdateExpr {
+    pub operator: UpdateOp,
+    // `++a` rather than `a++`
+    pub prefix: bool,
+    pub argument: Box<Expression>
+}
+// `a = b`, `a.b += c` or `[a, b] = c`, only a plain `=` destructures
+#[derive(Debug)]
+pub struct AssignExpr {
+    pub operator: AssignOp,
+    pub left: Box<Pattern>,
+    pub right: Box<Expression>
+}
+#[derive(Debug)]
+pub struct ConditionalExpr {
+    pub test: Box<Expression>,
+    pub consequent: Box<Expression>,
+    pub alternate: Box<Expression>
+}
+#[derive(Debug)]
+pub struct SequenceExpr {
+    pub expressions: Vec<Expression>
+}
+// `a.b`, `a[b]` or `a?.b`, a non-computed property is an identifier
+#[derive(Debug)]
+pub struct MemberExpr {
+    pub object: Box<Expression>,
+    pub property: Box<Expression>,
+    pub computed: bool,
+    pub optional: bool
+}
+#[derive(Debug)]
+pub struct CallExpr {
+    pub callee: Box<Expression>,
+    pub arguments: Vec<Expression>,
+    pub optional: bool
+}
+// `new X` without an argument list has no arguments
+#[derive(Debug)]
+pub struct NewExpr {
+    pub callee: Box<Expression>,
+    pub arguments: Vec<Expression>
+}
+#[derive(Debug)]
+pub struct ChainExpr {
+    pub expression: Box<Expression>
+}
+// quasis has one more element than expressions, they alternate starting with a quasi
+#[derive(Debug)]
+pub struct TemplateLiteral {
+    pub quasis: Vec<TemplateElement>,
+    pub expressions: Vec<Expression>
+}
+#[derive(Debug)]
+pub struct TemplateElement {
+    // None for an invalid escape in a tagged template
+    pub cooked: Option<JsString>,
+    pub raw: String
+}
+#[derive(Debug)]
+pub struct TaggedTemplateExpr {
+    pub tag: Box<Expression>,
+    pub quasi: TemplateLiteral
+}
+#[derive(Debug)]
+pub struct SpreadElement {
+    pub argument: Box<Expression>
+}
+// `[a, , ...b]`, a hole is None and a spread is a spread element
+#[derive(Debug)]
+pub struct ArrayExpr {
+    pub elements: Vec<Option<Expression>>
+}
+#[derive(Debug)]
+pub struct ObjectExpr {
+    pub properties: Vec<ObjectMember>
+}
+#[derive(Debug)]
+pub enum ObjectMember {
+    Property(Property),
+    // `{ ...a }`
+    Spread(SpreadElement)
+}
+// `a: 1`, `a`, `a() {}`, `get a() {}` or `set a(v) {}`. the value of
+// a method or accessor is a function expression
+#[derive(Debug)]
+pub struct Property {
+    pub key: PropertyKey,
+    pub value: Expression,
+    pub kind: PropertyKind,
+    pub method: bool,
+    pub shorthand: bool
+}
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PropertyKind {
+    Init,
+    Get,
+    Set
+}
+// `yield` alone has no argument, `yield* x` delegates to another iterator
+#[derive(Debug)]
+pub struct YieldExpr {
+    pub argument: Option<Box<Expression>>,
+    pub delegate: bool
+}
+#[derive(Debug)]
+pub struct AwaitExpr {
+    pub argument: Box<Expression>
+}
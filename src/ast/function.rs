@@ -0,0 +1,26 @@
+use super::expression::Expression;
+use super::statement::Statement;
+use super::variable::{Identifier, Pattern};
+
+// a function declaration, function expression or arrow function
+#[derive(Debug)]
+pub struct Function {
+    // None for anonymous function expressions and arrows
+    pub id: Option<Identifier>,
+    // a default value is an assign pattern, `function f(a = 1)`
+    pub params: Vec<Pattern>,
+    pub rest: Option<Box<Pattern>>,
+    pub body: FunctionBody,
+    pub is_async: bool,
+    pub generator: bool,
+    pub arrow: bool,
+    // the body is strict mode code, from a "use strict" directive or the code around it
+    pub strict: bool
+}
+
+#[derive(Debug)]
+pub enum FunctionBody {
+    Block(Vec<Statement>),
+    // `x => x + 1`
+    Expression(Box<Expression>)
+}
@@ -1,2 +1,4 @@
 pub mod expression;
-pub mod variable;
\ No newline at end of file
+pub mod variable;
+pub mod statement;
+pub mod function;
@@ -0,0 +1,138 @@
+use super::expression::Expression;
+use super::variable::{Identifier, VariableDeclaration, Pattern};
+use super::function::Function;
+
+#[derive(Debug)]
+pub struct Program {
+    pub body: Vec<Statement>,
+    // the source starts with a "use strict" directive
+    pub strict: bool
+}
+
+#[derive(Debug)]
+pub enum Statement {
+    Block(BlockStatement),
+    Empty,
+    Expression(ExpressionStatement),
+    VariableDeclaration(VariableDeclaration),
+    Function(Function),
+    If(IfStatement),
+    While(WhileStatement),
+    DoWhile(DoWhileStatement),
+    For(ForStatement),
+    ForIn(ForInStatement),
+    ForOf(ForOfStatement),
+    Switch(SwitchStatement),
+    Break(BreakStatement),
+    Continue(ContinueStatement),
+    Labeled(LabeledStatement),
+    Return(ReturnStatement),
+    Throw(ThrowStatement),
+    Try(TryStatement),
+    Debugger,
+    With(WithStatement)
+}
+
+#[derive(Debug)]
+pub struct BlockStatement {
+    pub body: Vec<Statement>
+}
+#[derive(Debug)]
+pub struct ExpressionStatement {
+    pub expression: Expression
+}
+#[derive(Debug)]
+pub struct IfStatement {
+    pub test: Expression,
+    pub consequent: Box<Statement>,
+    pub alternate: Option<Box<Statement>>
+}
+#[derive(Debug)]
+pub struct WhileStatement {
+    pub test: Expression,
+    pub body: Box<Statement>
+}
+#[derive(Debug)]
+pub struct DoWhileStatement {
+    pub body: Box<Statement>,
+    pub test: Expression
+}
+// the first part of `for (init; test; update)`, also the left side of for-in and for-of
+#[derive(Debug)]
+pub enum ForInit {
+    VariableDeclaration(VariableDeclaration),
+    Expression(Expression),
+    // `for ([a, b] of c)` or `for (a.b in c)`, assigned each iteration
+    Pattern(Pattern)
+}
+#[derive(Debug)]
+pub struct ForStatement {
+    pub init: Option<ForInit>,
+    pub test: Option<Expression>,
+    pub update: Option<Expression>,
+    pub body: Box<Statement>
+}
+#[derive(Debug)]
+pub struct ForInStatement {
+    pub left: ForInit,
+    pub right: Expression,
+    pub body: Box<Statement>
+}
+#[derive(Debug)]
+// `for await (a of b)` iterates an async iterator, only in async functions
+pub struct ForOfStatement {
+    pub left: ForInit,
+    pub right: Expression,
+    pub body: Box<Statement>,
+    pub is_await: bool
+}
+#[derive(Debug)]
+pub struct SwitchStatement {
+    pub discriminant: Expression,
+    pub cases: Vec<SwitchCase>
+}
+// `default:` has no test
+#[derive(Debug)]
+pub struct SwitchCase {
+    pub test: Option<Expression>,
+    pub consequent: Vec<Statement>
+}
+#[derive(Debug)]
+pub struct BreakStatement {
+    pub label: Option<Identifier>
+}
+#[derive(Debug)]
+pub struct ContinueStatement {
+    pub label: Option<Identifier>
+}
+#[derive(Debug)]
+pub struct LabeledStatement {
+    pub label: Identifier,
+    pub body: Box<Statement>
+}
+#[derive(Debug)]
+pub struct ReturnStatement {
+    pub argument: Option<Expression>
+}
+#[derive(Debug)]
+pub struct ThrowStatement {
+    pub argument: Expression
+}
+// at least one of handler and finalizer is there
+#[derive(Debug)]
+pub struct TryStatement {
+    pub block: BlockStatement,
+    pub handler: Option<CatchClause>,
+    pub finalizer: Option<BlockStatement>
+}
+// `catch {}` without a binding has no param
+#[derive(Debug)]
+pub struct CatchClause {
+    pub param: Option<Pattern>,
+    pub body: BlockStatement
+}
+#[derive(Debug)]
+pub struct WithStatement {
+    pub object: Expression,
+    pub body: Box<Statement>
+}
@@ -1,23 +1,115 @@
-use super::expression::Expression;
+use super::expression::{Expression, PropertyKey};
+use crate::atom::Atom;
 #[derive(Debug)]
 pub struct Identifier {
-    value: String
+    pub value: Atom
+}
+
+impl Identifier {
+    pub fn new(value: Atom) -> Self {
+        Self { value }
+    }
+}
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VariableKind {
+    Var,
+    Let,
+    Const
 }
 #[derive(Debug)]
 pub struct Variable {
-    name: Identifier,
-    init: Option<Expression>
+    pub id: Pattern,
+    pub init: Option<Expression>
 }
 #[derive(Debug)]
 pub struct VariableDeclaration {
+    pub kind: VariableKind,
     pub list: Vec<Variable>
 }
 
 impl Variable {
-    pub fn new(name: String, exp: Option<Expression>) -> Self {
+    pub fn new(id: Pattern, exp: Option<Expression>) -> Self {
         Self {
-            name: Identifier { value: name },
+            id,
             init: exp
         }
     } 
-}
\ No newline at end of file
+}
+
+// what a declaration binds or an assignment assigns, a name or a destructuring of the value
+#[derive(Debug)]
+pub enum Pattern {
+    Identifier(Identifier),
+    Object(ObjectPattern),
+    Array(ArrayPattern),
+    // `a = 1` inside a pattern, the default when the value is undefined
+    Assign(AssignPattern),
+    // `a.b` or `a[b]`, only as an assignment target, a declaration binds names
+    Member(Box<Expression>)
+}
+// `{ a, b: [c], [d]: e = 1, ...f }`. the rest of a binding pattern is a name,
+// in an assignment it may be a member access too
+#[derive(Debug)]
+pub struct ObjectPattern {
+    pub properties: Vec<PatternProperty>,
+    pub rest: Option<Box<Pattern>>
+}
+// `{ a }` is shorthand for `{ a: a }`
+#[derive(Debug)]
+pub struct PatternProperty {
+    pub key: PropertyKey,
+    pub value: Pattern,
+    pub shorthand: bool
+}
+// `[a, , b = 1, ...c]`, a hole is None
+#[derive(Debug)]
+pub struct ArrayPattern {
+    pub elements: Vec<Option<Pattern>>,
+    pub rest: Option<Box<Pattern>>
+}
+#[derive(Debug)]
+pub struct AssignPattern {
+    pub left: Box<Pattern>,
+    pub right: Box<Expression>
+}
+
+impl Pattern {
+    // the names bound by the pattern, in source order
+    pub fn bound_names(&self, names: &mut Vec<Atom>) {
+        match self {
+            Self::Identifier(id) => names.push(id.value),
+            Self::Object(obj) => {
+                for p in &obj.properties {
+                    p.value.bound_names(names);
+                }
+                if let Some(rest) = &obj.rest {
+                    rest.bound_names(names);
+                }
+            }
+            Self::Array(arr) => {
+                for e in arr.elements.iter().flatten() {
+                    e.bound_names(names);
+                }
+                if let Some(rest) = &arr.rest {
+                    rest.bound_names(names);
+                }
+            }
+            Self::Assign(a) => a.left.bound_names(names),
+            Self::Member(_) => ()
+        }
+    }
+    // only names, what a declaration or parameter list can bind
+    pub fn is_binding(&self) -> bool {
+        match self {
+            Self::Identifier(_) => true,
+            Self::Object(obj) => {
+                obj.properties.iter().all(|p| p.value.is_binding()) && obj.rest.as_ref().is_none_or(|r| r.is_binding())
+            }
+            Self::Array(arr) => {
+                arr.elements.iter().flatten().all(|e| e.is_binding()) && arr.rest.as_ref().is_none_or(|r| r.is_binding())
+            }
+            Self::Assign(a) => a.left.is_binding(),
+            Self::Member(_) => false
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{BuildHasherDefault, Hasher};
+use std::sync::{Mutex, OnceLock};
+
+// an interned name, comparing two atoms is comparing two integers.
+// the table is global and only grows, so a name lives as long as the program.
+// interning takes a lock, looking up the name of an atom doesn't
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Atom(u32);
+
+// names interned before anything else, atom n is PREINTERNED[n]
+const PREINTERNED: &[&str] = &[
+    "",
+    "length",
+    "prototype",
+    "constructor",
+    "__proto__",
+    "arguments",
+    "eval",
+    "undefined",
+    "name",
+    "toString",
+    "valueOf",
+    "let",
+];
+
+impl Atom {
+    pub const EMPTY: Atom = Atom(0);
+    pub const LENGTH: Atom = Atom(1);
+    pub const PROTOTYPE: Atom = Atom(2);
+    pub const CONSTRUCTOR: Atom = Atom(3);
+    pub const PROTO: Atom = Atom(4);
+    pub const ARGUMENTS: Atom = Atom(5);
+    pub const EVAL: Atom = Atom(6);
+    pub const UNDEFINED: Atom = Atom(7);
+    pub const NAME: Atom = Atom(8);
+    pub const TO_STRING: Atom = Atom(9);
+    pub const VALUE_OF: Atom = Atom(10);
+    pub const LET: Atom = Atom(11);
+
+    // the same name always gives the same atom
+    pub fn new(name: &str) -> Self {
+        let mut table = table().lock().unwrap();
+        if let Some(atom) = table.ids.get(name) {
+            return *atom;
+        }
+        table.insert(Box::leak(name.to_string().into_boxed_str()))
+    }
+    // names are written once before their atom is handed out, reading one needs no lock
+    pub fn as_str(&self) -> &'static str {
+        let (chunk, i) = slot(self.0);
+        NAMES[chunk].get().and_then(|c| c[i].get()).expect("an atom is named before it exists")
+    }
+}
+
+// names seen by one lexer, so an identifier that repeats in the source
+// only goes through the global table the first time
+#[derive(Default)]
+pub struct AtomCache<'a> {
+    ids: HashMap<&'a str, Atom, BuildHasherDefault<FnvHasher>>
+}
+
+impl<'a> AtomCache<'a> {
+    pub fn get(&mut self, name: &'a str) -> Atom {
+        if let Some(atom) = self.ids.get(name) {
+            return *atom;
+        }
+        let atom = Atom::new(name);
+        self.ids.insert(name, atom);
+        atom
+    }
+}
+
+// FNV-1a, names are short and the default SipHash is slow on them
+#[derive(Default)]
+struct FnvHasher(u64);
+
+impl Hasher for FnvHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        let mut hash = if self.0 == 0 { 0xcbf29ce484222325 } else { self.0 };
+        for b in bytes {
+            hash = (hash ^ *b as u64).wrapping_mul(0x100000001b3);
+        }
+        self.0 = hash;
+    }
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+struct AtomTable {
+    ids: HashMap<&'static str, Atom, BuildHasherDefault<FnvHasher>>,
+    len: u32
+}
+
+impl AtomTable {
+    fn insert(&mut self, name: &'static str) -> Atom {
+        let atom = Atom(self.len);
+        let (chunk, i) = slot(atom.0);
+        let names = NAMES[chunk].get_or_init(|| (0..FIRST_CHUNK << chunk).map(|_| OnceLock::new()).collect());
+        let _ = names[i].set(name);
+        self.len += 1;
+        self.ids.insert(name, atom);
+        atom
+    }
+}
+
+// the names by atom, in chunks that double in size and never move once allocated.
+// chunk k holds the atoms from FIRST_CHUNK * (2^k - 1) on
+const FIRST_CHUNK: usize = 64;
+const CHUNKS: usize = 27;
+#[allow(clippy::declare_interior_mutable_const)]
+const NO_CHUNK: OnceLock<Box<[OnceLock<&'static str>]>> = OnceLock::new();
+static NAMES: [OnceLock<Box<[OnceLock<&'static str>]>>; CHUNKS] = [NO_CHUNK; CHUNKS];
+
+// the chunk of an atom and its index in the chunk
+fn slot(atom: u32) -> (usize, usize) {
+    let n = atom as usize + FIRST_CHUNK;
+    let bit = usize::BITS - 1 - n.leading_zeros();
+    let chunk = bit as usize - FIRST_CHUNK.trailing_zeros() as usize;
+    (chunk, n - (1 << bit))
+}
+
+fn table() -> &'static Mutex<AtomTable> {
+    static TABLE: OnceLock<Mutex<AtomTable>> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let mut table = AtomTable { ids: HashMap::default(), len: 0 };
+        for name in PREINTERNED {
+            table.insert(name);
+        }
+        Mutex::new(table)
+    })
+}
+
+impl From<&str> for Atom {
+    fn from(s: &str) -> Self {
+        Self::new(s)
+    }
+}
+
+impl fmt::Display for Atom {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl fmt::Debug for Atom {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Atom({:?})", self.as_str())
+    }
+}
@@ -1,12 +1,11 @@
-use std::str::Chars;
-use std::iter::Peekable;
-
 // line ending
 pub const LF: char = '\u{a}';
 pub const CR: char = '\u{d}';
+pub const LS: char = '\u{2028}';
+pub const PS: char = '\u{2029}';
 
 fn is_line_break (s: char) -> bool {
-    return s == LF || s == CR;
+    s == LF || s == CR || s == LS || s == PS
 }
 #[derive(Debug)]
 pub struct Char {
@@ -15,44 +14,225 @@ pub struct Char {
     pub column: u64
 }
 
-#[derive(Debug)]
+// a point in the source, offset is in bytes from 0, line and columns count from 1.
+// `column` counts chars, editors and source maps want UTF-16 code units instead
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Position {
+    pub offset: usize,
+    pub line: u64,
+    pub column: u64,
+    pub column_utf16: u64,
+    pub column_byte: u64
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColumnUnit {
+    Utf8,
+    Utf16,
+    Char
+}
+
+impl Position {
+    pub fn column_in (&self, unit: ColumnUnit) -> u64 {
+        match unit {
+            ColumnUnit::Utf8 => self.column_byte,
+            ColumnUnit::Utf16 => self.column_utf16,
+            ColumnUnit::Char => self.column
+        }
+    }
+}
+
+// a range of source text, the end is exclusive
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Span {
+    pub start: Position,
+    pub end: Position
+}
+
+// reads the source as bytes, ASCII is handled without utf-8 decoding.
+// cloning is cheap, it only points into the source
+#[derive(Debug, Clone)]
 pub struct Code<'a> {
+    source: &'a str,
+    bytes: &'a [u8],
     pub line_cursor: u64,
-    iter: Peekable<Chars<'a>>,
     pub column_cursor: u64,
-    pub column_start: u64
+    pub column_utf16_cursor: u64,
+    // byte offset of the next char in source
+    pub offset: usize,
+    // byte offset where the current line starts
+    line_offset: usize,
+    // where the token being lexed starts
+    token_start: Position
 }
 
 impl<'a> Code<'a> {
     pub fn new (code: &'a str) -> Self {
         Self {
-            iter: code.chars().peekable(),
+            source: code,
+            bytes: code.as_bytes(),
             line_cursor: 1,
             column_cursor: 1,
-            column_start: 1
+            column_utf16_cursor: 1,
+            offset: 0,
+            line_offset: 0,
+            token_start: Position { offset: 0, line: 1, column: 1, column_utf16: 1, column_byte: 1 }
         }
     }
-    pub fn next (&mut self) -> Option<char> {
-        let nt = self.iter.next();
-        match nt {
-            Some(ch) => {
-                if is_line_break(ch) {
-                    self.line_cursor = self.line_cursor + 1;
-                    self.column_cursor = 1;
-                } else {
-                    self.column_cursor = self.column_cursor + 1
-                }
-            },
-            None => {}
+    pub fn position (&self) -> Position {
+        Position {
+            offset: self.offset,
+            line: self.line_cursor,
+            column: self.column_cursor,
+            column_utf16: self.column_utf16_cursor,
+            column_byte: (self.offset - self.line_offset) as u64 + 1
+        }
+    }
+    // remember the current position as the start of the next token
+    pub fn mark_start (&mut self) {
+        self.token_start = self.position();
+    }
+    pub fn token_start (&self) -> usize {
+        self.token_start.offset
+    }
+    // from the marked start to the current position
+    pub fn span (&self) -> Span {
+        Span {
+            start: self.token_start,
+            end: self.position()
         }
-        nt
     }
-    pub fn peek (&mut self) -> Option<char> {
-        let pk = self.iter.peek();
-        if let Some(ch) = pk {
-            Some(*ch)
+    // the whole line containing the byte offset, without its line terminator
+    pub fn line_text (&self, offset: usize) -> &'a str {
+        let start = self.source[..offset].rfind(is_line_break).map_or(0, |i| {
+            i + self.source[i..].chars().next().map_or(1, |c| c.len_utf8())
+        });
+        let end = self.source[offset..].find(is_line_break).map_or(self.source.len(), |i| offset + i);
+        &self.source[start..end]
+    }
+    // source text between two byte offsets
+    pub fn slice (&self, start: usize, end: usize) -> &'a str {
+        &self.source[start..end]
+    }
+    // the bytes not consumed yet
+    pub fn rest (&self) -> &'a [u8] {
+        &self.bytes[self.offset..]
+    }
+    // a cursor step rather than an iterator, it also updates the line and column
+    #[allow(clippy::should_implement_trait)]
+    pub fn next (&mut self) -> Option<char> {
+        let b = *self.bytes.get(self.offset)?;
+        if b < 0x80 {
+            self.offset += 1;
+            // CRLF is one line break, the CR just belongs to the line before
+            if b == b'\n' || (b == b'\r' && self.bytes.get(self.offset) != Some(&b'\n')) {
+                self.new_line();
+            } else {
+                self.column_cursor += 1;
+                self.column_utf16_cursor += 1;
+            }
+            return Some(b as char);
+        }
+        let ch = self.source[self.offset..].chars().next()?;
+        self.offset += ch.len_utf8();
+        if ch == LS || ch == PS {
+            self.new_line();
         } else {
-            None
+            self.column_cursor += 1;
+            self.column_utf16_cursor += ch.len_utf16() as u64;
+        }
+        Some(ch)
+    }
+    fn new_line (&mut self) {
+        self.line_cursor += 1;
+        self.line_offset = self.offset;
+        self.column_cursor = 1;
+        self.column_utf16_cursor = 1;
+    }
+    // consume n bytes the caller already checked are ASCII and not line terminators
+    pub fn skip_ascii (&mut self, n: usize) {
+        self.offset += n;
+        self.column_cursor += n as u64;
+        self.column_utf16_cursor += n as u64;
+    }
+    // consume ASCII bytes while f holds, f must never accept a line terminator.
+    // returns how many bytes were consumed
+    pub fn skip_ascii_while (&mut self, f: fn(u8) -> bool) -> usize {
+        let n = self.rest().iter().take_while(|b| **b < 0x80 && f(**b)).count();
+        self.skip_ascii(n);
+        n
+    }
+    // look n chars ahead without consuming, peek_nth(0) is the same as peek
+    pub fn peek_nth (&self, n: usize) -> Option<char> {
+        self.source[self.offset..].chars().nth(n)
+    }
+    pub fn peek (&self) -> Option<char> {
+        let b = *self.bytes.get(self.offset)?;
+        if b < 0x80 {
+            return Some(b as char);
         }
+        self.source[self.offset..].chars().next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the position after reading the whole source
+    fn end(source: &str) -> Position {
+        let mut code = Code::new(source);
+        while code.next().is_some() {}
+        code.position()
+    }
+
+    #[test]
+    fn line_breaks() {
+        assert_eq!(end("a\nb").line, 2);
+        assert_eq!(end("a\rb").line, 2);
+        // CRLF is a single break
+        assert_eq!(end("a\r\nb").line, 2);
+        assert_eq!(end("a\r\n\r\nb").line, 3);
+        assert_eq!(end("a\n\rb").line, 3);
+        assert_eq!(end("a\u{2028}b\u{2029}c").line, 3);
+        let p = end("ab\r\ncd");
+        assert_eq!((p.line, p.column, p.column_utf16, p.column_byte), (2, 3, 3, 3));
+    }
+
+    #[test]
+    fn columns_after_non_ascii() {
+        // é is one UTF-16 unit and two bytes
+        let p = end("é=");
+        assert_eq!((p.column, p.column_utf16, p.column_byte), (3, 3, 4));
+        // an astral char is two UTF-16 units and four bytes
+        let p = end("😀=");
+        assert_eq!((p.column, p.column_utf16, p.column_byte), (3, 4, 6));
+        assert_eq!(p.column_in(ColumnUnit::Utf16), 4);
+        // columns restart after a line break that isn't ASCII
+        let p = end("😀\u{2028}é");
+        assert_eq!((p.line, p.column, p.column_utf16, p.column_byte), (2, 2, 2, 3));
+    }
+
+    #[test]
+    fn span_from_marked_start() {
+        let mut code = Code::new("😀 ab");
+        code.next();
+        code.next();
+        code.mark_start();
+        code.next();
+        code.next();
+        let span = code.span();
+        assert_eq!((span.start.offset, span.end.offset), (5, 7));
+        assert_eq!((span.start.column, span.end.column), (3, 5));
+        assert_eq!((span.start.column_utf16, span.end.column_utf16), (4, 6));
+        assert_eq!((span.start.column_byte, span.end.column_byte), (6, 8));
+    }
+
+    #[test]
+    fn line_text_around_offset() {
+        let code = Code::new("a\r\nbé\u{2028}c");
+        assert_eq!(code.line_text(0), "a");
+        assert_eq!(code.line_text(3), "bé");
+        assert_eq!(code.line_text(9), "c");
     }
 }
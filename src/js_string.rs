@@ -0,0 +1,72 @@
+use std::fmt;
+
+// a javascript string value, a sequence of UTF-16 code units.
+// unlike a rust String it can hold lone surrogates like "\uD83D"
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct JsString {
+    units: Vec<u16>
+}
+
+impl JsString {
+    pub fn new() -> Self {
+        Self { units: vec![] }
+    }
+    pub fn push(&mut self, c: char) {
+        let mut buf = [0u16; 2];
+        self.units.extend_from_slice(c.encode_utf16(&mut buf));
+    }
+    // a code point from an escape sequence, a surrogate is kept as a single code unit,
+    // so "\uD83D\uDE00" ends up as the same pair as "😀"
+    pub fn push_code_point(&mut self, cp: u32) {
+        if cp <= 0xFFFF {
+            self.units.push(cp as u16);
+        } else if let Some(c) = char::from_u32(cp) {
+            self.push(c);
+        }
+    }
+    // length in code units, what `length` is in javascript
+    pub fn len(&self) -> usize {
+        self.units.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.units.is_empty()
+    }
+    pub fn as_units(&self) -> &[u16] {
+        &self.units
+    }
+    // lone surrogates become U+FFFD
+    pub fn to_string_lossy(&self) -> String {
+        String::from_utf16_lossy(&self.units)
+    }
+}
+
+impl From<&str> for JsString {
+    fn from(s: &str) -> Self {
+        Self { units: s.encode_utf16().collect() }
+    }
+}
+impl From<String> for JsString {
+    fn from(s: String) -> Self {
+        Self::from(s.as_str())
+    }
+}
+
+impl fmt::Display for JsString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_string_lossy())
+    }
+}
+
+// quoted like a rust string, lone surrogates are shown as \u{XXXX}
+impl fmt::Debug for JsString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"")?;
+        for c in char::decode_utf16(self.units.iter().cloned()) {
+            match c {
+                Ok(c) => write!(f, "{}", c.escape_debug())?,
+                Err(e) => write!(f, "\\u{{{:X}}}", e.unpaired_surrogate())?
+            }
+        }
+        write!(f, "\"")
+    }
+}
@@ -1,16 +1,98 @@
+use std::fmt;
+use crate::input::Span;
+
 #[derive(Debug)]
-pub enum LexerError {
+pub enum LexerErrorKind {
     InvalidUnicodeSequence,
     UnicodeOverfow,
     UnicodeSurrogateCodePoint,
     InvalidHexNumber,
     InvalidOctalNumber,
-    IllegalCharacter,
+    IllegalCharacter(char),
     UnexpectedKeyword(String),
     InvalidString,
     InvalidOctalSeq,
     EOF,
     InvalidBinaryNumber,
     InvalidNumberSeq,
-    UnexpectedToken(String)
-}
\ No newline at end of file
+    UnexpectedToken(String),
+    UnterminatedComment,
+    UnterminatedTemplate,
+    UnterminatedRegExp,
+    InvalidRegExpFlags(String),
+    InvalidBigInt(String),
+    InvalidNumericSeparator
+}
+
+impl LexerErrorKind {
+    pub fn message(&self) -> String {
+        match self {
+            Self::InvalidUnicodeSequence => "invalid unicode escape sequence".to_string(),
+            Self::UnicodeOverfow => "code point is larger than 0x10FFFF".to_string(),
+            Self::UnicodeSurrogateCodePoint => "surrogate code point is not allowed here".to_string(),
+            Self::InvalidHexNumber => "invalid hexadecimal digit".to_string(),
+            Self::InvalidOctalNumber => "invalid octal digit".to_string(),
+            Self::IllegalCharacter(c) => format!("illegal character {:?}", c),
+            Self::UnexpectedKeyword(k) => format!("unexpected keyword `{}`", k),
+            Self::InvalidString => "unterminated string literal".to_string(),
+            Self::InvalidOctalSeq => "invalid octal escape sequence".to_string(),
+            Self::EOF => "unexpected end of input".to_string(),
+            Self::InvalidBinaryNumber => "invalid binary digit".to_string(),
+            Self::InvalidNumberSeq => "invalid numeric literal".to_string(),
+            Self::UnexpectedToken(t) => format!("unexpected `{}`", t),
+            Self::UnterminatedComment => "unterminated comment".to_string(),
+            Self::UnterminatedTemplate => "unterminated template literal".to_string(),
+            Self::UnterminatedRegExp => "unterminated regular expression literal".to_string(),
+            Self::InvalidRegExpFlags(f) => format!("invalid regular expression flags `{}`", f),
+            Self::InvalidBigInt(m) => format!("invalid bigint literal, {}", m),
+            Self::InvalidNumericSeparator => "numeric separator must be between two digits".to_string()
+        }
+    }
+}
+
+// a lexer error with where it happened, the source line is kept for rendering
+#[derive(Debug)]
+pub struct LexerError {
+    pub kind: LexerErrorKind,
+    pub span: Span,
+    pub source_line: String
+}
+
+impl LexerError {
+    pub fn message(&self) -> String {
+        self.kind.message()
+    }
+}
+
+// error: invalid hexadecimal digit
+//  --> 1:9
+//   |
+// 1 | let a = 0xZ;
+//   |         ^^
+impl fmt::Display for LexerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        render(f, &self.message(), &self.span, &self.source_line)
+    }
+}
+
+// the message with the source line under it and the span underlined,
+// parser errors are printed the same way
+pub fn render(f: &mut fmt::Formatter<'_>, message: &str, span: &Span, source_line: &str) -> fmt::Result {
+    let start = span.start;
+    let end = span.end;
+    let line_no = start.line.to_string();
+    let pad = " ".repeat(line_no.len());
+    let width = if end.line == start.line && end.column > start.column {
+        (end.column - start.column) as usize
+    } else if end.line == start.line {
+        1
+    } else {
+        // a multi-line span is underlined to the end of its first line
+        (source_line.chars().count() + 1).saturating_sub(start.column as usize).max(1)
+    };
+    writeln!(f, "error: {}", message)?;
+    writeln!(f, "{}--> {}:{}", pad, start.line, start.column)?;
+    writeln!(f, "{} |", pad)?;
+    writeln!(f, "{} | {}", line_no, source_line)?;
+    write!(f, "{} | {}{}", pad, " ".repeat((start.column as usize).saturating_sub(1)), "^".repeat(width))
+}
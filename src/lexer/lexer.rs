@@ -1,164 +1,397 @@
+use std::borrow::Cow;
 use super::token::*;
 use crate::input::Code;
-use super::error::LexerError;
+use super::error::{LexerError, LexerErrorKind};
 use super::util::*;
+use crate::js_string::JsString;
+use crate::atom::{Atom, AtomCache};
 
 
+// the lexical goal symbol, only the parser knows where a regexp literal may start
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum LexGoal {
+    // `/` and `/=` are division punctuators
+    InputElementDiv,
+    // `/` starts a regular expression literal
+    InputElementRegExp
+}
+
+// the lexer state between two tokens, rewinding to it re-lexes from there
+#[derive(Debug, Clone)]
+pub struct Checkpoint<'a> {
+    code: Code<'a>,
+    template_braces: Vec<usize>,
+    goal: LexGoal,
+    newline_seen: bool
+}
+
 pub struct Lexer<'a> {
     code: Code<'a>,
-    cache: String
+    // cooked value of a string literal or template piece
+    string_cache: JsString,
+    // the string being lexed has a legacy octal or \8 \9 escape
+    octal_escape_seen: bool,
+    // emit comments as trivia tokens instead of skipping them
+    keep_comments: bool,
+    // one entry per open template substitution, counting the braces opened inside it,
+    // a `}` seen when the count is 0 closes the substitution
+    template_braces: Vec<usize>,
+    goal: LexGoal,
+    // a line terminator was skipped since the last non-comment token
+    newline_seen: bool,
+    // the iterator has reached the end of input or an error
+    finished: bool,
+    // identifiers interned so far, by their source text
+    atoms: AtomCache<'a>
 }
 
 impl<'a> Lexer<'a> {
     pub fn new (code: &'a str) -> Self {
         Self {
             code: Code::new(code),
-            cache: String::new()
+            string_cache: JsString::new(),
+            octal_escape_seen: false,
+            keep_comments: false,
+            template_braces: vec![],
+            goal: LexGoal::InputElementDiv,
+            newline_seen: false,
+            finished: false,
+            atoms: AtomCache::default()
+        }
+    }
+    pub fn keep_comments (&mut self, keep: bool) {
+        self.keep_comments = keep;
+    }
+    pub fn set_goal (&mut self, goal: LexGoal) {
+        self.goal = goal;
+    }
+    pub fn goal (&self) -> LexGoal {
+        self.goal
+    }
+    // the source line around a byte offset, for error messages
+    pub fn line_text (&self, offset: usize) -> &'a str {
+        self.code.line_text(offset)
+    }
+    pub fn checkpoint (&self) -> Checkpoint<'a> {
+        Checkpoint {
+            code: self.code.clone(),
+            template_braces: self.template_braces.clone(),
+            goal: self.goal,
+            newline_seen: self.newline_seen
         }
     }
-    pub fn advance (&mut self) -> Result<Token, LexerError> {
+    pub fn rewind (&mut self, cp: Checkpoint<'a>) {
+        self.code = cp.code;
+        self.template_braces = cp.template_braces;
+        self.goal = cp.goal;
+        self.newline_seen = cp.newline_seen;
+        self.finished = false;
+        self.string_cache = JsString::new();
+        self.octal_escape_seen = false;
+    }
+    // LexerError keeps the source line for rendering, it is only built once per failed lex
+    #[allow(clippy::result_large_err)]
+    pub fn advance (&mut self) -> Result<Token<'a>, LexerError> {
+        self.scan().map_err(|kind| {
+            self.string_cache = JsString::new();
+            self.octal_escape_seen = false;
+            self.error(kind)
+        })
+    }
+    // attach the position to an error, it spans from the token start to where lexing stopped
+    fn error (&self, kind: LexerErrorKind) -> LexerError {
+        let span = self.code.span();
+        LexerError {
+            kind,
+            span,
+            source_line: self.code.line_text(span.start.offset).to_string()
+        }
+    }
+    fn scan (&mut self) -> Result<Token<'a>, LexerErrorKind> {
+        // a hashbang is only allowed at the very start of the source
+        if self.code.offset == 0 && self.code.peek() == Some('#') && self.code.peek_nth(1) == Some('!') {
+            self.code.mark_start();
+            self.code.next();
+            self.code.next();
+            self.single_line_comment();
+            if self.keep_comments {
+                return Ok(self.set_comment_token(TokenType::HASHBANG_COMMENT, 0));
+            }
+        }
         while let Some(ch) = self.code.peek() {
+            self.code.mark_start();
             match ch {
-                LF | CR | PS | LS | TAB | FF | NBSP | SPACE => {
+                LF | CR | PS | LS => {
                     self.code.next();
+                    self.newline_seen = true;
                     continue;
                 }
-                '=' => {
-                    return Ok(self.set_one_char_token(ch, TokenType::ASSIGN));
-                }
-                ':' => {
-                    return Ok(self.set_one_char_token(ch, TokenType::COLON));
-                }
-                '[' => {
-                    return Ok(self.set_one_char_token(ch, TokenType::LBRACK));
-                }
-                ']' => {
-                    return Ok(self.set_one_char_token(ch, TokenType::RBRACK));
+                TAB | FF | NBSP | SPACE => {
+                    self.code.next();
+                    continue;
                 }
-                '(' => {
-                    return Ok(self.set_one_char_token(ch, TokenType::LPAREN));
+                '/' if self.code.peek_nth(1) == Some('/') => {
+                    self.code.next();
+                    self.code.next();
+                    self.single_line_comment();
+                    if self.keep_comments {
+                        return Ok(self.set_comment_token(TokenType::SINGLE_LINE_COMMENT, 0));
+                    }
+                    continue;
                 }
-                ')' => {
-                    return Ok(self.set_one_char_token(ch, TokenType::RPAREN));
+                '/' if self.code.peek_nth(1) == Some('*') => {
+                    self.code.next();
+                    self.code.next();
+                    self.multi_line_comment()?;
+                    if self.keep_comments {
+                        return Ok(self.set_comment_token(TokenType::MULTI_LINE_COMMENT, 2));
+                    }
+                    continue;
                 }
-                '{' => {
-                    return Ok(self.set_one_char_token(ch, TokenType::LBRACE));
+                '/' if self.goal == LexGoal::InputElementRegExp => {
+                    self.code.next();
+                    return self.regexp_literal();
                 }
-                '}' => {
-                    return Ok(self.set_one_char_token(ch, TokenType::RBRACE));
+                '`' => {
+                    self.code.next();
+                    return self.template_literal(true);
                 }
-                ',' => {
-                    return Ok(self.set_one_char_token(ch, TokenType::COMMA));
+                '}' if self.template_braces.last() == Some(&0) => {
+                    self.template_braces.pop();
+                    self.code.next();
+                    return self.template_literal(false);
                 }
                 '"' | '\'' => {
-                    self.set_column_start();
                     self.code.next();
                     return self.string_literal(ch);
                 }
-                'a'..='z' | 'A'..='Z' | '_' | '$' => {
-                    self.set_column_start();
+                'a'..='z' | 'A'..='Z' | '_' | '$' | '\\' => {
                     return self.handle_identifier();
                 }
-                ';' => {
-                    return Ok(self.set_one_char_token(ch, TokenType::SEMICOLON));
+                c if !c.is_ascii() && is_identifier_start(c) => {
+                    return self.handle_identifier();
                 }
                 '0' => {
                     return self.handle_number_start_with_zero();
                 }
                 '1'..='9' => {
-                    return self.handle_decimal_numeric(false);
+                    return self.handle_decimal_numeric();
                 }
-                _ => {
+                '.' if matches!(self.code.peek_nth(1), Some('0'..='9')) => {
+                    return self.handle_decimal_numeric();
+                }
+                c if is_punctuator_start(c) => {
+                    return self.handle_punctuator();
+                }
+                c => {
                     self.code.next();
-                    break;
+                    return Err(LexerErrorKind::IllegalCharacter(c));
                 }
             }
         }
-        Err(LexerError::EOF)
+        self.code.mark_start();
+        Err(LexerErrorKind::EOF)
     }
-    fn set_column_start(&mut self) {
-        self.code.column_start = self.code.column_cursor;
+    // consume until a line terminator, the terminator itself is left for advance
+    fn single_line_comment(&mut self) {
+        while let Some(c) = self.code.peek() {
+            if is_line_terminator(c) {
+                break;
+            }
+            self.code.next();
+        }
     }
-    fn set_one_char_token(&mut self, ch: char, tp: TokenType) -> Token {
-        self.set_column_start();
-        self.code.next();
-        Token {
-            value: ch.to_string(),
-            category: tp,
-            line: self.code.line_cursor,
-            column: self.code.column_start,
-            number: None
+    // consume until `*/`, line breaks are counted by Code::next
+    fn multi_line_comment(&mut self) -> Result<(), LexerErrorKind> {
+        while let Some(c) = self.code.next() {
+            if c == '*' && self.code.peek() == Some('/') {
+                self.code.next();
+                return Ok(());
+            }
+            if is_line_terminator(c) {
+                self.newline_seen = true;
+            }
+        }
+        Err(LexerErrorKind::UnterminatedComment)
+    }
+    fn handle_punctuator(&mut self) -> Result<Token<'a>, LexerErrorKind> {
+        match match_punctuator(self.code.rest()) {
+            Some((len, tp)) => {
+                self.code.skip_ascii(len);
+                if let Some(depth) = self.template_braces.last_mut() {
+                    match tp {
+                        TokenType::LBRACE => *depth += 1,
+                        TokenType::RBRACE => *depth -= 1,
+                        _ => ()
+                    }
+                }
+                Ok(self.set_token(tp))
+            }
+            None => {
+                let c = self.code.next().unwrap_or_default();
+                Err(LexerErrorKind::UnexpectedToken(c.to_string()))
+            }
         }
     }
     // pub fn get_token (&mut self) -> Option<Token> {
     //     self.current.take()
     // }
-    pub fn set_token (&mut self, tp: TokenType) -> Token {
-        let v = self.cache.clone();
-        let num = parse_numeric(&v, &tp).ok();
-        self.cache = String::new();
+    // the token's value is its source text
+    pub fn set_token (&mut self, tp: TokenType) -> Token<'a> {
+        let v = self.code.slice(self.code.token_start(), self.code.offset);
+        self.set_token_value(tp, Cow::Borrowed(v))
+    }
+    fn set_token_value (&mut self, tp: TokenType, v: Cow<'a, str>) -> Token<'a> {
+        let newline_before = self.newline_seen;
+        // comments are trivia, the flag is kept for the token after them
+        if !is_comment_token(tp) {
+            self.newline_seen = false;
+        }
         Token {
             value: v,
             category: tp,
-            line: self.code.line_cursor,
-            column: self.code.column_start,
-            number: num
+            span: self.code.span(),
+            newline_before,
+            keyword: None,
+            escaped: false,
+            atom: None,
+            number: None,
+            string: None,
+            octal_escape: false,
+            raw: None,
+            escape_error: None,
+            regex_flags: None,
+            bigint: None
         }
     }
-    fn handle_decimal_numeric (&mut self, radix_prefix: bool)-> Result<Token, LexerError>{
-        let mut has_radix = false;
-        while let Some(nt) = self.code.peek() {
-            match nt {
-                '0'..='9' => {
-                    self.accept(nt);
-                    self.code.next();
-                }
-                '.' => {
-                    if radix_prefix || has_radix {
-                        return Err(LexerError::UnexpectedToken(" . ".to_string()))
-                    } else {
-                        has_radix = true;
-                        self.accept('.');
-                        self.code.next();
-                    }
-                }
-                '_' => {
-                    self.code.next();
-                }
-                _ => {
-                    break;
-                }
+    // a comment's value is its text between the delimiters, `//` or `/*` and `*/`
+    fn set_comment_token (&mut self, tp: TokenType, closing: usize) -> Token<'a> {
+        let v = self.code.slice(self.code.token_start() + 2, self.code.offset - closing);
+        self.set_token_value(tp, Cow::Borrowed(v))
+    }
+    // the digits of the numeric literal being lexed, without the radix prefix,
+    // the bigint suffix and separators
+    fn numeric_digits (&self, prefix: usize, suffix: usize) -> Cow<'a, str> {
+        let s = self.code.slice(self.code.token_start() + prefix, self.code.offset - suffix);
+        if s.contains('_') {
+            Cow::Owned(s.replace('_', ""))
+        } else {
+            Cow::Borrowed(s)
+        }
+    }
+    // the number is parsed with the radix tag, a radix literal itself is a plain numeric literal
+    fn set_numeric_token (&mut self, tp: TokenType, radix: TokenType, prefix: usize) -> Token<'a> {
+        let num = parse_numeric(&self.numeric_digits(prefix, 0), &radix).ok();
+        let mut token = self.set_token(tp);
+        token.number = num;
+        token
+    }
+    fn set_bigint_token (&mut self, radix: TokenType, prefix: usize) -> Token<'a> {
+        let big = parse_bigint(&self.numeric_digits(prefix, 1), &radix).ok();
+        let mut token = self.set_token(TokenType::BIGINT_LITERAL);
+        token.bigint = big;
+        token
+    }
+    // consume the `n` suffix of a bigint literal if there is one
+    fn bigint_suffix (&mut self) -> bool {
+        if self.code.peek() == Some('n') {
+            self.code.next();
+            return true;
+        }
+        false
+    }
+    // DecimalLiteral: digits [. digits] [exponent] or . digits [exponent]
+    fn handle_decimal_numeric (&mut self)-> Result<Token<'a>, LexerErrorKind>{
+        let mut integer = true;
+        self.decimal_digits()?;
+        if self.code.peek() == Some('.') {
+            self.code.next();
+            self.decimal_digits()?;
+            integer = false;
+        }
+        if self.exponent_part()? {
+            integer = false;
+        }
+        if self.bigint_suffix() {
+            if !integer {
+                return Err(LexerErrorKind::InvalidBigInt("a bigint literal must be an integer".to_string()));
+            }
+            self.check_after_numeric()?;
+            return Ok(self.set_bigint_token(TokenType::NUMERIC_LITERAL_DECIMAL, 0));
+        }
+        self.check_after_numeric()?;
+        Ok(self.set_numeric_token(TokenType::NUMERIC_LITERAL, TokenType::NUMERIC_LITERAL, 0))
+    }
+    fn decimal_digits (&mut self) -> Result<usize, LexerErrorKind> {
+        self.digits(|c| c.is_ascii_digit())
+    }
+    // e|E [+|-] digits, at least one digit is required.
+    // returns whether there was an exponent
+    fn exponent_part (&mut self) -> Result<bool, LexerErrorKind> {
+        match self.code.peek() {
+            Some('e' | 'E') => {
+                self.code.next();
             }
+            _ => return Ok(false)
         }
-        Ok(self.set_token(TokenType::NUMERIC_LITERAL))
+        if let Some('+' | '-') = self.code.peek() {
+            self.code.next();
+        }
+        if self.code.peek() == Some('_') {
+            return Err(self.separator_error());
+        }
+        if !matches!(self.code.peek(), Some('0'..='9')) {
+            return Err(LexerErrorKind::InvalidNumberSeq);
+        }
+        self.decimal_digits()?;
+        Ok(true)
     }
-    fn handle_number_start_with_zero(&mut self) -> Result<Token, LexerError>{
+    fn handle_number_start_with_zero(&mut self) -> Result<Token<'a>, LexerErrorKind>{
         self.code.next();
         match self.code.peek() {
             Some('O') | Some('o') => {
                 self.code.next();
                 self.octal_number()?;
-                return Ok(self.set_token(TokenType::NUMERIC_LITERAL));
+                if self.bigint_suffix() {
+                    self.check_after_numeric()?;
+                    return Ok(self.set_bigint_token(TokenType::NUMERIC_LITERAL_OCTAL, 2));
+                }
+                self.check_after_numeric()?;
+                Ok(self.set_numeric_token(TokenType::NUMERIC_LITERAL, TokenType::NUMERIC_LITERAL_OCTAL, 2))
             }
             Some('0'..='9') => {
-                return self.octal_or_decimal_number();
+                self.octal_or_decimal_number()
             }
             Some('b') | Some('B') => {
                 self.code.next();
                 self.binary_number()?;
-                return Ok(self.set_token(TokenType::NUMERIC_LITERAL));
+                if self.bigint_suffix() {
+                    self.check_after_numeric()?;
+                    return Ok(self.set_bigint_token(TokenType::NUMERIC_LITERAL_BINARY, 2));
+                }
+                self.check_after_numeric()?;
+                Ok(self.set_numeric_token(TokenType::NUMERIC_LITERAL, TokenType::NUMERIC_LITERAL_BINARY, 2))
             }
             Some('x') | Some('X') => {
                 self.code.next();
                 self.hex_number()?;
-                return Ok(self.set_token(TokenType::NUMERIC_LITERAL));
+                if self.bigint_suffix() {
+                    self.check_after_numeric()?;
+                    return Ok(self.set_bigint_token(TokenType::NUMERIC_LITERAL_HEX, 2));
+                }
+                self.check_after_numeric()?;
+                Ok(self.set_numeric_token(TokenType::NUMERIC_LITERAL, TokenType::NUMERIC_LITERAL_HEX, 2))
+            }
+            Some('_') => {
+                Err(self.separator_error())
             }
+            // 0, 0.5, 0e1
             _ => {
-                Err(LexerError::InvalidNumberSeq)
+                self.handle_decimal_numeric()
             }
         }
     }
-    fn octal_or_decimal_number(&mut self) -> Result<Token, LexerError>{
+    fn octal_or_decimal_number(&mut self) -> Result<Token<'a>, LexerErrorKind>{
         let mut is_decimal = false;
         loop {
             let pc = self.code.peek();
@@ -167,15 +400,14 @@ impl<'a> Lexer<'a> {
                     match c {
                         '0'..='7' => {
                             self.code.next();
-                            self.accept(c);
                         }
                         '8'..='9' => {
                             self.code.next();
                             is_decimal = true;
-                            self.accept(c);
                         }
+                        // separators are not allowed in legacy octal-like literals
                         '_' => {
-                            self.code.next();
+                            return Err(self.separator_error());
                         }
                         _ => {
                             break;
@@ -187,149 +419,272 @@ impl<'a> Lexer<'a> {
                 }
             }
         }
-        self.check_after_numeric()?;
+        if self.code.peek() == Some('n') {
+            return Err(LexerErrorKind::InvalidBigInt("a legacy octal-like literal can't be a bigint".to_string()));
+        }
         if is_decimal {
-            return Ok(self.set_token(TokenType::NUMERIC_LITERAL));
+            // NonOctalDecimalIntegerLiteral is still a decimal literal, `08.5` and `09e1` are fine
+            if self.code.peek() == Some('.') {
+                self.code.next();
+                self.decimal_digits()?;
+            }
+            self.exponent_part()?;
+            self.check_after_numeric()?;
+            let tp = TokenType::NON_OCTAL_DECIMAL_LITERAL;
+            Ok(self.set_numeric_token(tp, tp, 0))
         } else {
-            return Ok(self.set_token(TokenType::NUMERIC_LITERAL));
+            self.check_after_numeric()?;
+            let tp = TokenType::LEGACY_OCTAL_LITERAL;
+            Ok(self.set_numeric_token(tp, tp, 0))
         }
     }
-    fn octal_number(&mut self) -> Result<(), LexerError>{
-        loop {
-            let pc = self.code.peek();
-            match pc {
-                Some(c) => {
-                    match c {
-                        '0'..='7' => {
-                            self.code.next();
-                            self.accept(c);
-                        }
-                        '_' => {
-                            self.code.next();
-                        }
-                        _ => {
-                            break;
-                        }
-                    }
-                }
-                None => {
-                    break;
+    fn octal_number(&mut self) -> Result<(), LexerErrorKind>{
+        if self.digits(|c| matches!(c, '0'..='7'))? == 0 {
+            return Err(LexerErrorKind::InvalidOctalNumber);
+        }
+        Ok(())
+    }
+    fn binary_number(&mut self) -> Result<(), LexerErrorKind>{
+        if self.digits(|c| matches!(c, '0'..='1'))? == 0 {
+            return Err(LexerErrorKind::InvalidBinaryNumber);
+        }
+        Ok(())
+    }
+    fn hex_number(&mut self) -> Result<(), LexerErrorKind>{
+        if self.digits(|c| c.is_ascii_hexdigit())? == 0 {
+            return Err(LexerErrorKind::InvalidHexNumber);
+        }
+        Ok(())
+    }
+    // digits of one radix with numeric separators, returns how many digits were read.
+    // a `_` is only allowed between two digits, so `1__0`, `1_`, `0x_1` and `1_.5` are rejected
+    fn digits(&mut self, is_digit: fn(char) -> bool) -> Result<usize, LexerErrorKind> {
+        let mut count = 0;
+        let mut last_digit = false;
+        while let Some(c) = self.code.peek() {
+            if is_digit(c) {
+                self.code.next();
+                count += 1;
+                last_digit = true;
+            } else if c == '_' {
+                let next_digit = self.code.peek_nth(1).is_some_and(is_digit);
+                if !last_digit || !next_digit {
+                    return Err(self.separator_error());
                 }
+                self.code.next();
+                last_digit = false;
+            } else {
+                break;
+            }
+        }
+        Ok(count)
+    }
+    // the `_` is consumed so the error span ends right after it
+    fn separator_error(&mut self) -> LexerErrorKind {
+        self.code.next();
+        LexerErrorKind::InvalidNumericSeparator
+    }
+    // the char after a numeric literal can't be an IdentifierStart or a digit,
+    // a `\` would start an escaped one as in `1\u0061`
+    fn check_after_numeric(&mut self) -> Result<(), LexerErrorKind> {
+        let nc = self.code.peek();
+        if let Some(c) = nc {
+            if c == '\\' {
+                return Err(LexerErrorKind::InvalidNumberSeq);
+            }
+            if is_identifier_start(c) || c.is_ascii_digit() {
+                return Err(LexerErrorKind::UnexpectedToken(c.to_string()));
             }
         }
-        self.check_after_numeric()
+        Ok(())
     }
-    fn binary_number(&mut self) -> Result<(), LexerError>{
+    // the value borrows the source until the first escape, from there the cooked
+    // value is built in the string cache
+    pub fn string_literal (&mut self, start: char) -> Result<Token<'a>, LexerErrorKind> {
+        let body_start = self.code.offset;
+        let mut cooked = false;
         loop {
-            let pc = self.code.peek();
-            match pc {
+            let end = self.code.offset;
+            let nt = self.code.next();
+            match nt {
+                Some('\n') | Some('\r') => {
+                    return Err(LexerErrorKind::InvalidString);
+                }
+                Some(c) if c == start => {
+                    return Ok(self.set_string_token(TokenType::STRING_LITERAL, body_start, end, cooked));
+                }
+                Some('\\') => {
+                    if !cooked {
+                        self.start_cooking(body_start, end);
+                        cooked = true;
+                    }
+                    self.handle_string_seq(false)?;
+                }
                 Some(c) => {
-                    match c {
-                        '0'..='1' => {
-                            self.code.next();
-                            self.accept(c);
-                        }
-                        '_' => {
-                            self.code.next();
-                        }
-                        _ => {
-                            break;
-                        }
+                    if cooked {
+                        self.accept_str(c);
                     }
                 }
                 None => {
-                    break;
+                    return Err(LexerErrorKind::InvalidString);
                 }
             }
         }
-        self.check_after_numeric()
     }
-    fn hex_number(&mut self) -> Result<(), LexerError>{
+    // a template piece, starts after '`' (head) or after the '}' closing a substitution.
+    // the cooked value goes to the string cache, the raw value is sliced from the source
+    fn template_literal (&mut self, head: bool) -> Result<Token<'a>, LexerErrorKind> {
+        let raw_start = self.code.offset;
+        let mut cooked = false;
+        let mut escape_error = None;
+        let tp;
+        let raw_end;
         loop {
-            let pc = self.code.peek();
-            match pc {
-                Some(c) => {
-                    match c {
-                        '0'..='9' | 'a'..='f' | 'A'..='F' => {
-                            self.code.next();
-                            self.accept(c);
-                        }
-                        '_' => {
-                            self.code.next();
-                        }
-                        _ => {
-                            break;
+            let end = self.code.offset;
+            match self.code.next() {
+                Some('`') => {
+                    raw_end = end;
+                    tp = if head { TokenType::TEMPLATE_NO_SUBSTITUTION } else { TokenType::TEMPLATE_TAIL };
+                    break;
+                }
+                Some('$') if self.code.peek() == Some('{') => {
+                    self.code.next();
+                    raw_end = end;
+                    tp = if head { TokenType::TEMPLATE_HEAD } else { TokenType::TEMPLATE_MIDDLE };
+                    self.template_braces.push(0);
+                    break;
+                }
+                Some('\\') => {
+                    if !cooked {
+                        self.start_cooking(raw_start, end);
+                        cooked = true;
+                    }
+                    // keep scanning after a bad escape, the rest of it is plain template text
+                    if let Err(e) = self.handle_string_seq(true) {
+                        if escape_error.is_none() {
+                            escape_error = Some(e);
                         }
                     }
                 }
+                // CR and CRLF are normalized to LF
+                Some(CR) => {
+                    if !cooked {
+                        self.start_cooking(raw_start, end);
+                        cooked = true;
+                    }
+                    if self.code.peek() == Some(LF) {
+                        self.code.next();
+                    }
+                    self.accept_str(LF);
+                }
+                Some(c) => {
+                    if cooked {
+                        self.accept_str(c);
+                    }
+                }
                 None => {
-                    break;
+                    return Err(LexerErrorKind::UnterminatedTemplate);
                 }
             }
         }
-        self.check_after_numeric()
-    }
-    fn check_after_numeric(&mut self) -> Result<(), LexerError> {
-        let nc = self.code.peek();
-        if let Some(c) = nc {
-            if is_identifier_start(c) || c.is_digit(10) {
-                return Err(LexerError::UnexpectedToken(c.to_string()));
-            }
+        let raw = self.code.slice(raw_start, raw_end);
+        let raw = if raw.contains('\r') {
+            Cow::Owned(raw.replace("\r\n", "\n").replace('\r', "\n"))
+        } else {
+            Cow::Borrowed(raw)
+        };
+        let mut token = self.set_string_token(tp, raw_start, raw_end, cooked);
+        if escape_error.is_some() {
+            token.value = Cow::Borrowed("");
+            token.string = None;
         }
-        Ok(())
+        token.raw = Some(raw);
+        token.escape_error = escape_error.map(|kind| self.error(kind));
+        Ok(token)
     }
-    pub fn string_literal (&mut self, start: char) -> Result<Token, LexerError> {
+    // a regexp literal after the leading '/', the value is the body.
+    // a '/' inside a class like `[/]` doesn't end the body, and a line terminator is never allowed
+    fn regexp_literal (&mut self) -> Result<Token<'a>, LexerErrorKind> {
+        let body_start = self.code.offset;
+        let mut in_class = false;
+        let body_end;
         loop {
-            let nt = self.code.next();
-            match nt {
-                Some('\n') | Some('\r') => {
-                    return Err(LexerError::InvalidString);
+            let end = self.code.offset;
+            match self.code.next() {
+                Some('/') if !in_class => {
+                    body_end = end;
+                    break;
                 }
-                Some(c @ '"') | Some(c @ '\'') => {
-                    if c == start {
-                        return Ok(self.set_token(TokenType::STRING_LITERAL));
-                    } else {
-                        self.accept(c);
-                    }
+                Some('[') => {
+                    in_class = true;
+                }
+                Some(']') => {
+                    in_class = false;
                 }
                 Some('\\') => {
-                    self.handle_string_seq()?;
+                    match self.code.next() {
+                        Some(c) if !is_line_terminator(c) => (),
+                        _ => {
+                            return Err(LexerErrorKind::UnterminatedRegExp);
+                        }
+                    }
                 }
-                Some(c) => {
-                    self.accept(c);
+                Some(c) if !is_line_terminator(c) => (),
+                _ => {
+                    return Err(LexerErrorKind::UnterminatedRegExp);
                 }
-                None => break
             }
         }
-        return Ok(self.set_token(TokenType::STRING_LITERAL));
+        let flags_start = self.code.offset;
+        while let Some(c) = self.code.peek() {
+            if c == '\\' {
+                self.code.next();
+                let flags = self.code.slice(flags_start, self.code.offset).to_string();
+                return Err(LexerErrorKind::InvalidRegExpFlags(flags));
+            }
+            if !is_identifier_continue(c) {
+                break;
+            }
+            self.code.next();
+        }
+        let flags = self.code.slice(flags_start, self.code.offset);
+        if !valid_regexp_flags(flags) {
+            return Err(LexerErrorKind::InvalidRegExpFlags(flags.to_string()));
+        }
+        let body = self.code.slice(body_start, body_end);
+        let mut token = self.set_token_value(TokenType::REGEXP_LITERAL, Cow::Borrowed(body));
+        token.regex_flags = Some(flags);
+        Ok(token)
     }
-    pub fn handle_string_seq (&mut self) -> Result<Token, LexerError> {
+    // escape sequence after a '\\', the cooked char is pushed to the string cache.
+    // templates don't allow legacy octal escapes
+    pub fn handle_string_seq (&mut self, in_template: bool) -> Result<(), LexerErrorKind> {
         match self.code.next() {
             None => {
-                return Err(LexerError::InvalidString);
+                return Err(LexerErrorKind::InvalidString);
             }
             Some(c) => match c {
                 // special escape char
                 'b' => {
-                    self.accept('\u{8}');
+                    self.accept_str('\u{8}');
                 }
                 't' => {
-                    self.accept('\u{9}');
+                    self.accept_str('\u{9}');
                 }
                 'n' => {
-                    self.accept('\u{a}');
+                    self.accept_str('\u{a}');
                 }
                 'v' => {
-                    self.accept('\u{b}');
+                    self.accept_str('\u{b}');
                 }
                 'f' => {
-                    self.accept('\u{c}');
+                    self.accept_str('\u{c}');
                 }
                 'r' => {
-                    self.accept('\u{d}');
+                    self.accept_str('\u{d}');
                 }
                 '\'' | '\"' | '\\' => {
-                    self.accept(c);
+                    self.accept_str(c);
                 }
                 // \xXX, must be followed by 2 hex char
                 'x' => {
@@ -348,137 +703,171 @@ impl<'a> Lexer<'a> {
                                 }
                             }
                         } else {
-                            return Err(LexerError::InvalidOctalSeq);
+                            return Err(LexerErrorKind::InvalidOctalSeq);
                         }
                     }
                     let t = digit_to_char(v)?;
-                    self.accept(t);
+                    self.accept_str(t);
                 },
-                // \xxx, 0-3 octal number
+                '0' if !matches!(self.code.peek(), Some('0'..='9')) => {
+                    self.accept_str('\u{0}');
+                }
+                '0'..='9' if in_template => {
+                    return Err(LexerErrorKind::InvalidOctalSeq);
+                }
+                // NonOctalDecimalEscapeSequence, just the digit but not allowed in strict mode
+                '8' | '9' => {
+                    self.octal_escape_seen = true;
+                    self.accept_str(c);
+                }
+                // LegacyOctalEscapeSequence, up to 3 digits when it starts with 0-3, else up to 2.
+                // `\08` is \0 followed by '8'
                 '0'..='7' => {
-                    let mut v: u32 = 0;
-                    let mut tmp: u32 = 0;
-                    tmp = tmp << 3 | (c as u32 - 48);
-                    for _ in 0..2 {
-                        let pn = self.code.peek();
-                        if let Some(n) = pn {
-                            match n {
-                                '0'..='7' => {
-                                    let to_num = n as u32 - 48;
-                                    tmp = tmp << 3 | to_num;
-                                    if tmp > 255 {
-                                        break;
-                                    } else {
-                                        self.code.next();
-                                        v = tmp;
-                                    }
-                                },
-                                _ => {
-                                    break;
-                                }
+                    self.octal_escape_seen = true;
+                    let max_len = if c <= '3' { 3 } else { 2 };
+                    let mut v: u32 = c as u32 - '0' as u32;
+                    for _ in 1..max_len {
+                        match self.code.peek() {
+                            Some(n @ '0'..='7') => {
+                                self.code.next();
+                                v = v << 3 | (n as u32 - '0' as u32);
                             }
-                        } else {
-                            break;
-                        }
-                    }
-                    let c = char::try_from(v)
-                    .map_err(|_e| LexerError::InvalidOctalSeq);
-                    match c {
-                        Ok(cc) => {
-                            self.accept(cc);
-                        }
-                        Err(e) => {
-                            return Err(e);
+                            _ => break
                         }
                     }
+                    self.string_cache.push_code_point(v);
                 }
-                // \uxxxx or \u{xxxx}
+                // \uxxxx or \u{xxxx}, surrogates are fine in strings,
+                // a pair of surrogate escapes ends up as one char
                 'u' => {
                     let pn = self.code.peek();
                     match pn {
                         Some('{') => {
                             self.code.next();
-                            let c = self.try_code_point();
-                            match c {
-                                Err(e) => {
-                                    return Err(e);
-                                }
-                                Ok(c) => {
-                                    self.accept(c);
-                                    self.code.next();
-                                }
+                            let v = self.code_point_value()?;
+                            if self.code.next() != Some('}') {
+                                return Err(LexerErrorKind::InvalidUnicodeSequence);
                             }
+                            self.string_cache.push_code_point(v);
                         },
                         Some(_) => {
-                            let c = self.try_four_hex_num();
-                            match c {
-                                Err(e) => {
-                                    return Err(e);
-                                }
-                                Ok(c) => {
-                                    self.accept(c);
-                                }
-                            }
+                            let v = self.four_hex_value()?;
+                            self.string_cache.push_code_point(v);
                         }
                         None => {
-                            return Err(LexerError::InvalidUnicodeSequence);
+                            return Err(LexerErrorKind::InvalidUnicodeSequence);
                         }
                     }
                 }
+                // line continuation, contributes nothing
+                CR => {
+                    if self.code.peek() == Some(LF) {
+                        self.code.next();
+                    }
+                }
+                LF | LS | PS => {}
                 other => {
-                    self.accept(other);
+                    self.accept_str(other);
                 }
 
             }
         }
-        Ok(self.set_token(TokenType::STRING_LITERAL))
+        Ok(())
     }
-    pub fn handle_identifier (&mut self) -> Result<Token, LexerError>{
-        while let Some(c) = &self.code.peek() {
-            let nt = *c;
-            if !is_identifier_continue(nt) {
-                if nt == '\\' {
-                    let v = self.handle_unicode_seq()?;
-                    if !is_identifier_continue(v) {
-                        return Err(LexerError::InvalidUnicodeSequence);
+    // IdentifierName, every char may also be written as a \uXXXX or \u{X} escape,
+    // an escape must decode to a char that is valid where it stands
+    // the name borrows the source, only an escape makes it an owned string
+    pub fn handle_identifier (&mut self) -> Result<Token<'a>, LexerErrorKind>{
+        let start = self.code.offset;
+        let mut name: Option<String> = None;
+        let mut first = true;
+        loop {
+            // ASCII fast path
+            if !first {
+                let run = self.code.offset;
+                if self.code.skip_ascii_while(is_ascii_identifier_continue) > 0 {
+                    if let Some(n) = name.as_mut() {
+                        n.push_str(self.code.slice(run, self.code.offset));
                     }
-                    continue;
-                } else {
-                    break;
                 }
-            } else {
-                self.accept(nt);
+            }
+            let c = match self.code.peek() {
+                Some(c) => c,
+                None => break
+            };
+            if c == '\\' {
+                let here = self.code.offset;
                 self.code.next();
+                let v = self.handle_unicode_seq()?;
+                let valid = if first { is_identifier_start(v) } else { is_identifier_continue(v) };
+                if !valid {
+                    return Err(LexerErrorKind::InvalidUnicodeSequence);
+                }
+                name.get_or_insert_with(|| self.code.slice(start, here).to_string()).push(v);
+            } else if (first && is_identifier_start(c)) || (!first && is_identifier_continue(c)) {
+                self.code.next();
+                if let Some(n) = name.as_mut() {
+                    n.push(c);
+                }
+            } else {
+                break;
             }
+            first = false;
         }
-        Ok(self.token_finishup())
+        let value = match name {
+            Some(n) => Cow::Owned(n),
+            None => Cow::Borrowed(self.code.slice(start, self.code.offset))
+        };
+        Ok(self.token_finishup(value))
     }
-    pub fn token_finishup (&mut self) -> Token {
-        let kd = try_keyword(&self.cache);
-        return self.set_token(kd);
+    // an escaped reserved word like `\u0069f` is not the keyword,
+    // it becomes an identifier remembering the keyword so the parser can reject it
+    pub fn token_finishup (&mut self, value: Cow<'a, str>) -> Token<'a> {
+        let escaped = matches!(value, Cow::Owned(_));
+        let mut kd = try_keyword(&value);
+        let mut keyword = try_contextual_keyword(&value);
+        if escaped && kd != TokenType::Identifier {
+            keyword = Some(kd);
+            kd = TokenType::Identifier;
+        }
+        let atom = match &value {
+            _ if kd != TokenType::Identifier => None,
+            Cow::Borrowed(name) => Some(self.atoms.get(name)),
+            Cow::Owned(name) => Some(Atom::from(name.as_str()))
+        };
+        let mut token = self.set_token_value(kd, value);
+        token.atom = atom;
+        token.keyword = keyword;
+        token.escaped = escaped;
+        token
     }
-    pub fn handle_unicode_seq (&mut self) -> Result<char, LexerError> {
-        if let Some(next) = self.code.next() {
-            if next == 'u' {
-                match self.code.peek() {
-                    Some('{') => {
-                        return self.try_code_point();
-                    }
-                    Some(_) => {
-                        return self.try_four_hex_num();
-                    }
-                    None => {
-                        return Err(LexerError::InvalidUnicodeSequence);
-                    }
+    // \uXXXX or \u{X...}, the '\\' is already consumed
+    pub fn handle_unicode_seq (&mut self) -> Result<char, LexerErrorKind> {
+        if self.code.next() != Some('u') {
+            return Err(LexerErrorKind::InvalidUnicodeSequence);
+        }
+        match self.code.peek() {
+            Some('{') => {
+                self.code.next();
+                let c = self.try_code_point()?;
+                if self.code.next() != Some('}') {
+                    return Err(LexerErrorKind::InvalidUnicodeSequence);
                 }
-            } else {
-                Err(LexerError::InvalidUnicodeSequence)
+                Ok(c)
             }
-        } else {
-            Err(LexerError::InvalidUnicodeSequence)
+            Some(_) => self.try_four_hex_num(),
+            None => Err(LexerErrorKind::InvalidUnicodeSequence)
         }
     }
-    pub fn try_four_hex_num (&mut self) -> Result<char, LexerError>{
+    pub fn try_four_hex_num (&mut self) -> Result<char, LexerErrorKind>{
+        let v = self.four_hex_value()?;
+        digit_to_char(v)
+    }
+    pub fn try_code_point (&mut self) -> Result<char, LexerErrorKind> {
+        let v = self.code_point_value()?;
+        digit_to_char(v)
+    }
+    fn four_hex_value (&mut self) -> Result<u32, LexerErrorKind>{
         let mut v: u32 = 0;
         for _ in 0..4 {
             let nt = self.code.next();
@@ -486,29 +875,405 @@ impl<'a> Lexer<'a> {
                 let dg = hex_char_to_digit(ch)?;
                 v = v << 4 | dg;
             } else {
-                return Err(LexerError::InvalidUnicodeSequence);
+                return Err(LexerErrorKind::InvalidUnicodeSequence);
             }
         }
-        digit_to_char(v)
+        Ok(v)
     }
-    pub fn try_code_point (&mut self) -> Result<char, LexerError> {
+    // hex digits of a \u{...} escape, up to 0x10FFFF
+    fn code_point_value (&mut self) -> Result<u32, LexerErrorKind> {
         let mut v: u32 = 0;
+        let mut digits = 0;
         loop {
             let n = match self.code.peek() {
                 Some(c @ '0'..='9') => c as u32 - '0' as u32,
                 Some(c @ 'a'..='f') => 10 + (c as u32 - 'a' as u32),
                 Some(c @ 'A'..='F') => 10 + (c as u32 - 'A' as u32),
                 None => {
-                    return Err(LexerError::InvalidUnicodeSequence)
+                    return Err(LexerErrorKind::InvalidUnicodeSequence)
                 },
                 Some(_) => break,
             };
             self.code.next();
-            v = v << 4 | n;
+            digits += 1;
+            // stop growing once out of range
+            if v <= 0x10FFFF {
+                v = v << 4 | n;
+            }
         }
-        digit_to_char(v)
+        if digits == 0 {
+            return Err(LexerErrorKind::InvalidUnicodeSequence);
+        }
+        if v > 0x10FFFF {
+            return Err(LexerErrorKind::UnicodeOverfow);
+        }
+        Ok(v)
+    }
+    fn accept_str (&mut self, c: char) {
+        self.string_cache.push(c);
+    }
+    // the first escape in a string, what came before it starts the cooked value
+    fn start_cooking (&mut self, start: usize, end: usize) {
+        self.string_cache = JsString::from(self.code.slice(start, end));
+    }
+    // without escapes the value is the source between start and end.
+    // a cooked value is exact in `string`, `value` gets its lossy text
+    fn set_string_token (&mut self, tp: TokenType, start: usize, end: usize, cooked: bool) -> Token<'a> {
+        let mut token = if cooked {
+            let st = std::mem::take(&mut self.string_cache);
+            let mut token = self.set_token_value(tp, Cow::Owned(st.to_string_lossy()));
+            token.string = Some(st);
+            token
+        } else {
+            let v = self.code.slice(start, end);
+            self.set_token_value(tp, Cow::Borrowed(v))
+        };
+        token.octal_escape = self.octal_escape_seen;
+        self.octal_escape_seen = false;
+        token
+    }
+}
+
+// the token stream ends with None at the end of input, and stops after the first error
+impl<'a> Iterator for Lexer<'a> {
+    type Item = Result<Token<'a>, LexerError>;
+    fn next (&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        match self.advance() {
+            Ok(t) => Some(Ok(t)),
+            Err(e) => {
+                self.finished = true;
+                if let LexerErrorKind::EOF = e.kind {
+                    return None;
+                }
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num_bigint::BigInt;
+
+    fn lex(source: &str) -> Result<Vec<Token<'_>>, LexerErrorKind> {
+        Lexer::new(source).collect::<Result<_, _>>().map_err(|e| e.kind)
+    }
+
+    // the value of a source that is a single numeric literal
+    fn number(source: &str) -> f64 {
+        let tokens = lex(source).unwrap_or_else(|e| panic!("{}: {}", source, e.message()));
+        assert_eq!(tokens.len(), 1, "{}", source);
+        assert_eq!(tokens[0].category, TokenType::NUMERIC_LITERAL, "{}", source);
+        tokens[0].number.unwrap_or_else(|| panic!("{} has no value", source))
+    }
+
+    #[test]
+    fn decimal_forms() {
+        assert_eq!(number("1.5e-3"), 1.5e-3);
+        assert_eq!(number(".5"), 0.5);
+        assert_eq!(number("5."), 5.0);
+        assert_eq!(number("1E10"), 1e10);
+        assert_eq!(number("1e+2"), 100.0);
+        assert_eq!(number("0.0"), 0.0);
+        assert_eq!(number("0e5"), 0.0);
+    }
+
+    #[test]
+    fn decimal_is_correctly_rounded() {
+        let cases = [
+            "0.1",
+            "0.30000000000000004",
+            // halfway between two doubles, ties go to even
+            "9007199254740993",
+            "9007199254740995",
+            "2.2250738585072011e-308",
+            "2.2250738585072012e-308",
+            "4.9e-324",
+            "2.4703282292062328e-324",
+            "1.7976931348623157e308",
+            "123456789012345678901234567890",
+            "0.000000000000000000000000000001"
+        ];
+        for source in cases {
+            assert_eq!(number(source).to_bits(), source.parse::<f64>().unwrap().to_bits(), "{}", source);
+        }
+        assert_eq!(number("1e400"), f64::INFINITY);
+        assert_eq!(number("1e-400"), 0.0);
+    }
+
+    #[test]
+    fn decimal_needs_exponent_digits() {
+        assert!(lex("1e").is_err());
+        assert!(lex("1e+").is_err());
+        // `.` alone, or before a letter, is not a number
+        let tokens = lex(".e1").unwrap();
+        assert_eq!(tokens[0].category, TokenType::PERIOD);
+    }
+
+    #[test]
+    fn identifier_right_after_number() {
+        for source in ["3in", "1a", "0x1g", "0b12", "1n2", "1e1a"] {
+            assert!(matches!(lex(source), Err(LexerErrorKind::UnexpectedToken(_))), "{}", source);
+        }
+        for source in ["1\\u0061", "1\\u{61}", "0x1\\u0061", "1n\\u0061", "017\\u0031", "1.5\\u0061"] {
+            assert!(matches!(lex(source), Err(LexerErrorKind::InvalidNumberSeq)), "{}", source);
+        }
+    }
+
+    #[test]
+    fn numeric_separators_between_digits() {
+        assert_eq!(number("1_000"), 1000.0);
+        assert_eq!(number("1_0.0_1e1_0"), 10.01e10);
+        assert_eq!(number("0b1_0"), 2.0);
+        assert_eq!(number("0o7_7"), 63.0);
+        assert_eq!(number("0x1_F"), 31.0);
+        let tokens = lex("1_0n").unwrap();
+        assert_eq!(tokens[0].category, TokenType::BIGINT_LITERAL);
+    }
+
+    #[test]
+    fn numeric_separators_rejected() {
+        let cases = ["1__0", "1_", "0x_1", "0b_1", "0o1_", "1._5", "1_.5", "1e_5", "1e5_", "0_7", "07_1", "08_1", "0_1"];
+        for source in cases {
+            assert!(matches!(lex(source), Err(LexerErrorKind::InvalidNumericSeparator)), "{}", source);
+        }
+    }
+
+    // whether each token has a line terminator before it
+    fn newlines(source: &str) -> Vec<bool> {
+        lex(source).unwrap().iter().map(|t| t.newline_before).collect()
+    }
+
+    #[test]
+    fn newline_before_each_line_terminator() {
+        for source in ["a\nb", "a\rb", "a\r\nb", "a\u{2028}b", "a\u{2029}b"] {
+            assert_eq!(newlines(source), [false, true], "{:?}", source);
+        }
+        assert_eq!(newlines("a b"), [false, false]);
+    }
+
+    #[test]
+    fn newline_inside_comments() {
+        assert_eq!(newlines("a /*\n*/ b"), [false, true]);
+        assert_eq!(newlines("a /* */ b"), [false, false]);
+        assert_eq!(newlines("a // c\nb"), [false, true]);
+        // the flag carries over the comment to the next token
+        assert_eq!(newlines("a\n/* */ b"), [false, true]);
+    }
+
+    // category, cooked value and raw value of each template piece, other tokens by category only
+    fn templates(source: &str) -> Vec<(TokenType, String, String)> {
+        lex(source).unwrap().into_iter().map(|t| {
+            let raw = t.raw.as_deref().unwrap_or_default().to_string();
+            (t.category, t.value.to_string(), raw)
+        }).collect()
+    }
+
+    fn piece(category: TokenType, cooked: &str, raw: &str) -> (TokenType, String, String) {
+        (category, cooked.to_string(), raw.to_string())
+    }
+
+    #[test]
+    fn template_cooked_and_raw() {
+        assert_eq!(templates("`a\\nb`"), [piece(TokenType::TEMPLATE_NO_SUBSTITUTION, "a\nb", "a\\nb")]);
+        assert_eq!(templates("`\\u{41}\\x41`"), [piece(TokenType::TEMPLATE_NO_SUBSTITUTION, "AA", "\\u{41}\\x41")]);
+        // CR and CRLF become LF in both values
+        assert_eq!(templates("`a\r\nb\rc`"), [piece(TokenType::TEMPLATE_NO_SUBSTITUTION, "a\nb\nc", "a\nb\nc")]);
+        // a bad escape has no cooked value but keeps its raw text, for tagged templates
+        let tokens = lex("`\\unicode`").unwrap();
+        assert!(tokens[0].escape_error.is_some());
+        assert_eq!(tokens[0].value, "");
+        assert_eq!(tokens[0].raw.as_deref(), Some("\\unicode"));
+    }
+
+    #[test]
+    fn template_substitutions() {
+        assert_eq!(templates("`a${b}c${d}e`"), [
+            piece(TokenType::TEMPLATE_HEAD, "a", "a"),
+            piece(TokenType::Identifier, "b", ""),
+            piece(TokenType::TEMPLATE_MIDDLE, "c", "c"),
+            piece(TokenType::Identifier, "d", ""),
+            piece(TokenType::TEMPLATE_TAIL, "e", "e")
+        ]);
+        // a template inside a substitution
+        assert_eq!(templates("`a${ `b${c}` }d`"), [
+            piece(TokenType::TEMPLATE_HEAD, "a", "a"),
+            piece(TokenType::TEMPLATE_HEAD, "b", "b"),
+            piece(TokenType::Identifier, "c", ""),
+            piece(TokenType::TEMPLATE_TAIL, "", ""),
+            piece(TokenType::TEMPLATE_TAIL, "d", "d")
+        ]);
+        // the `}` of an object literal doesn't close the substitution
+        let categories: Vec<_> = templates("`${ {a: 1} }b`").into_iter().map(|t| t.0).collect();
+        assert_eq!(categories, [
+            TokenType::TEMPLATE_HEAD,
+            TokenType::LBRACE,
+            TokenType::Identifier,
+            TokenType::COLON,
+            TokenType::NUMERIC_LITERAL,
+            TokenType::RBRACE,
+            TokenType::TEMPLATE_TAIL
+        ]);
+    }
+
+    #[test]
+    fn template_unterminated() {
+        for source in ["`abc", "`a${b}c", "`${ `b` }", "`a\\`"] {
+            assert!(matches!(lex(source), Err(LexerErrorKind::UnterminatedTemplate)), "{:?}", source);
+        }
+    }
+
+    #[test]
+    fn regexp_body_and_flags() {
+        let mut lexer = Lexer::new("/[/]\\//gi");
+        lexer.set_goal(LexGoal::InputElementRegExp);
+        let token = lexer.next().unwrap().unwrap();
+        assert_eq!(token.category, TokenType::REGEXP_LITERAL);
+        assert_eq!(token.value, "[/]\\/");
+        assert_eq!(token.regex_flags, Some("gi"));
+        assert!(lexer.next().is_none());
+    }
+
+    // the first error when lexing a single regexp literal
+    fn regexp_error(source: &str) -> LexerErrorKind {
+        let mut lexer = Lexer::new(source);
+        lexer.set_goal(LexGoal::InputElementRegExp);
+        match lexer.next() {
+            Some(Err(e)) => e.kind,
+            t => panic!("{:?} lexed as {:?}", source, t)
+        }
+    }
+
+    #[test]
+    fn regexp_errors() {
+        for source in ["/a\nb/", "/a\\\n/", "/[\u{2028}]/", "/a"] {
+            assert!(matches!(regexp_error(source), LexerErrorKind::UnterminatedRegExp), "{:?}", source);
+        }
+        for source in ["/a/gg", "/a/x", "/a/uv", "/a/\\u0067"] {
+            assert!(matches!(regexp_error(source), LexerErrorKind::InvalidRegExpFlags(_)), "{:?}", source);
+        }
+    }
+
+    #[test]
+    fn slash_is_division_by_default() {
+        let categories: Vec<_> = lex("a / b /= c").unwrap().iter().map(|t| t.category).collect();
+        assert_eq!(categories, [
+            TokenType::Identifier,
+            TokenType::DIV,
+            TokenType::Identifier,
+            TokenType::DIV_ASSIGN,
+            TokenType::Identifier
+        ]);
+    }
+
+    // the value of a source that is a single bigint literal
+    fn bigint(source: &str) -> BigInt {
+        let tokens = lex(source).unwrap_or_else(|e| panic!("{}: {}", source, e.message()));
+        assert_eq!(tokens.len(), 1, "{}", source);
+        assert_eq!(tokens[0].category, TokenType::BIGINT_LITERAL, "{}", source);
+        tokens[0].bigint.clone().unwrap_or_else(|| panic!("{} has no value", source))
+    }
+
+    #[test]
+    fn bigint_radixes() {
+        assert_eq!(bigint("123n"), BigInt::from(123));
+        assert_eq!(bigint("0n"), BigInt::from(0));
+        assert_eq!(bigint("0x1fn"), BigInt::from(31));
+        assert_eq!(bigint("0o7n"), BigInt::from(7));
+        assert_eq!(bigint("0b1n"), BigInt::from(1));
+        assert_eq!(bigint("0b101n"), BigInt::from(5));
+        // values past 2^64 keep every digit
+        assert_eq!(bigint("18446744073709551617n"), BigInt::from(u64::MAX) + 2);
+        assert_eq!(bigint("0x1_0000_0000_0000_0000n"), BigInt::from(u64::MAX) + 1);
+    }
+
+    #[test]
+    fn bigint_rejected() {
+        for source in ["1.5n", "1e3n", ".5n", "01n", "08n"] {
+            assert!(matches!(lex(source), Err(LexerErrorKind::InvalidBigInt(_))), "{}", source);
+        }
+    }
+
+    // category and value of a source that is a single legacy octal-like literal
+    fn legacy(source: &str) -> (TokenType, f64) {
+        let tokens = lex(source).unwrap_or_else(|e| panic!("{}: {}", source, e.message()));
+        assert_eq!(tokens.len(), 1, "{}", source);
+        (tokens[0].category, tokens[0].number.unwrap())
+    }
+
+    #[test]
+    fn legacy_octal_and_non_octal_decimal() {
+        assert_eq!(legacy("017"), (TokenType::LEGACY_OCTAL_LITERAL, 15.0));
+        assert_eq!(legacy("00"), (TokenType::LEGACY_OCTAL_LITERAL, 0.0));
+        assert_eq!(legacy("019"), (TokenType::NON_OCTAL_DECIMAL_LITERAL, 19.0));
+        assert_eq!(legacy("08.5"), (TokenType::NON_OCTAL_DECIMAL_LITERAL, 8.5));
+        assert_eq!(legacy("09e1"), (TokenType::NON_OCTAL_DECIMAL_LITERAL, 90.0));
+        // a legacy octal has no fraction, `017.5` is 15 followed by .5
+        let tokens = lex("017.5").unwrap();
+        assert_eq!(tokens.len(), 2);
+        assert_eq!(tokens[1].number, Some(0.5));
+    }
+
+    #[test]
+    fn non_decimal_is_rounded_once() {
+        let cases = [
+            ("0777777777777777777777", 0o777777777777777777777u64 as f64),
+            // adding digit by digit in f64 gets these one ulp off
+            ("0100552301110005422206", 1.165671519827142e18),
+            ("0o7345455514372150616225161", 3.517415160888408e22),
+            ("0x100000000000008010000000000", 2.0282409603651675e31),
+            ("0x20000000000001", 9007199254740992.0),
+            ("0x20000000000003", 9007199254740996.0)
+        ];
+        for (source, value) in cases {
+            let tokens = lex(source).unwrap();
+            assert_eq!(tokens[0].number.unwrap().to_bits(), value.to_bits(), "{}", source);
+        }
+    }
+
+    // UTF-16 units of a source that is a single string literal, and whether it has an octal escape
+    fn string(source: &str) -> (Vec<u16>, bool) {
+        let tokens = lex(source).unwrap_or_else(|e| panic!("{}: {}", source, e.message()));
+        assert_eq!(tokens.len(), 1, "{}", source);
+        assert_eq!(tokens[0].category, TokenType::STRING_LITERAL, "{}", source);
+        (tokens[0].cooked().unwrap().as_units().to_vec(), tokens[0].octal_escape)
     }
-    pub fn accept (&mut self, tk: char) {
-        self.cache.push(tk);
+
+    #[test]
+    fn string_escapes() {
+        // a lone surrogate is kept as is, it can't be held in a rust string
+        assert_eq!(string(r#""\uD800""#), (vec![0xD800], false));
+        assert_eq!(string(r#""😀""#), (vec![0xD83D, 0xDE00], false));
+        assert_eq!(string(r#""\u{1F600}""#), (vec![0xD83D, 0xDE00], false));
+        assert_eq!(string("'\\x41\\u{41}'"), (vec![0x41, 0x41], false));
+        // a line continuation adds nothing
+        assert_eq!(string("'a\\\nb'"), (vec![0x61, 0x62], false));
+        assert_eq!(string("'a\\\r\nb'"), (vec![0x61, 0x62], false));
+        assert_eq!(string("'a\\\u{2028}b'"), (vec![0x61, 0x62], false));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn string_octal_escapes() {
+        // \0 not followed by a digit is NUL, not a legacy octal escape
+        assert_eq!(string(r"'\0'"), (vec![0], false));
+        assert_eq!(string(r"'\0a'"), (vec![0, 0x61], false));
+        assert_eq!(string(r"'\00'"), (vec![0], true));
+        assert_eq!(string(r"'\07'"), (vec![7], true));
+        assert_eq!(string(r"'\101'"), (vec![0x41], true));
+        assert_eq!(string(r"'\400'"), (vec![0x20, 0x30], true));
+        // \8 and \9 are the chars themselves, but strict mode rejects them too
+        assert_eq!(string(r"'\8'"), (vec![0x38], true));
+        assert_eq!(string(r"'\9'"), (vec![0x39], true));
+    }
+
+    #[test]
+    fn string_unterminated() {
+        for source in ["'abc", "'a\nb'", "\"a\rb\"", "'\\"] {
+            assert!(matches!(lex(source), Err(LexerErrorKind::InvalidString)), "{:?}", source);
+        }
+    }
+}
@@ -1,4 +1,5 @@
 pub mod token;
 pub mod error;
 mod util;
+#[allow(clippy::module_inception)]
 pub mod lexer;
\ No newline at end of file
@@ -1,7 +1,13 @@
-use regex::Regex;
 use unicode_xid::UnicodeXID;
-use crate::lexer::error::LexerError;
-#[derive(Debug, PartialEq)]
+use std::borrow::Cow;
+use num_bigint::BigInt;
+use crate::lexer::error::{LexerError, LexerErrorKind};
+use crate::input::Span;
+use crate::js_string::JsString;
+use crate::atom::Atom;
+// named after the spec's tokens, like NUMERIC_LITERAL
+#[allow(non_camel_case_types)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum TokenType {
     // keywords
     BREAK,
@@ -29,17 +35,48 @@ pub enum TokenType {
     INSTANCEOF,
     IN,
     NEW,
+    CONST,
+    CLASS,
+    EXTENDS,
+    SUPER,
+    IMPORT,
+    EXPORT,
+    VOID,
+    DEBUGGER,
+    ENUM,
+    // contextual keywords, lexed as identifiers with `keyword` set
+    ASYNC,
+    AWAIT,
+    YIELD,
+    OF,
+    GET,
+    SET,
+    STATIC,
+    // reserved words in strict mode, lexed as identifiers with `keyword` set
+    IMPLEMENTS,
+    INTERFACE,
+    PACKAGE,
+    PRIVATE,
+    PROTECTED,
+    PUBLIC,
     // literal
     FALSE_LITERAL,
     TRUE_LITERAL,
     NULL_LITERAL,
-    UNDEFINED_LITERAL,
     NUMERIC_LITERAL,
     STRING_LITERAL,
+    TEMPLATE_NO_SUBSTITUTION, // `...`
+    TEMPLATE_HEAD, // `...${
+    TEMPLATE_MIDDLE, // }...${
+    TEMPLATE_TAIL, // }...`
+    REGEXP_LITERAL, // /pattern/flags
     NUMERIC_LITERAL_BINARY,
     NUMERIC_LITERAL_OCTAL,
     NUMERIC_LITERAL_DECIMAL,
     NUMERIC_LITERAL_HEX,
+    BIGINT_LITERAL, // 123n
+    LEGACY_OCTAL_LITERAL, // 010, forbidden in strict mode
+    NON_OCTAL_DECIMAL_LITERAL, // 089, forbidden in strict mode
     // punctuator
     SEMICOLON,
     PERIOD, // .
@@ -67,15 +104,46 @@ pub enum TokenType {
     AND, // &&
     OR, // ||
     ASSIGN, // =
+    ELLIPSIS, // ...
+    QUESTION, // ?
+    OPTIONAL_CHAIN, // ?.
+    NULLISH, // ??
+    ARROW, // =>
+    MUL, // *
+    DIV, // /
+    MOD, // %
+    EXP, // **
+    BIT_AND, // &
+    BIT_OR, // |
+    BIT_XOR, // ^
+    BIT_NOT, // ~
+    SHL, // <<
+    SAR, // >>
+    SHR, // >>>
+    ADD_ASSIGN, // +=
+    SUB_ASSIGN, // -=
+    MUL_ASSIGN, // *=
+    DIV_ASSIGN, // /=
+    MOD_ASSIGN, // %=
+    EXP_ASSIGN, // **=
+    SHL_ASSIGN, // <<=
+    SAR_ASSIGN, // >>=
+    SHR_ASSIGN, // >>>=
+    BIT_AND_ASSIGN, // &=
+    BIT_OR_ASSIGN, // |=
+    BIT_XOR_ASSIGN, // ^=
+    AND_ASSIGN, // &&=
+    OR_ASSIGN, // ||=
+    NULLISH_ASSIGN, // ??=
+    // trivia, only emitted when the lexer keeps comments
+    SINGLE_LINE_COMMENT, // // ...
+    MULTI_LINE_COMMENT, // /* ... */
+    HASHBANG_COMMENT, // #! ...
     UNHANDLED,
     Identifier
 }
 
 
-pub const Number_Regex: &str = r"\d";
-pub const Punctuators_Regex: &str = r"\+|\-|=";
-pub const Emoji_Regex: &str = r"\p{Emoji}";
-
 // special unicode chars
 // ZERO WIDTH NON-JOINER, in identifiers
 pub const ZWNJ: char = '\u{200c}';
@@ -96,112 +164,287 @@ pub const CR: char = '\u{d}';
 pub const LS: char = '\u{2028}';
 pub const PS: char = '\u{2029}';
 
+// values borrow from the source unless escapes made them differ from it
 #[derive(Debug)]
-pub struct Token {
-    pub value: String,
+pub struct Token<'a> {
+    // the source text, the cooked text of a string or an identifier with escapes,
+    // a comment without its delimiters
+    pub value: Cow<'a, str>,
     pub number: Option<f64>,
     pub category: TokenType,
-    pub line: u64,
-    pub column: u64
+    pub span: Span,
+    // the contextual keyword or strict mode reserved word an identifier spells,
+    // like `async` or `let`, the parser decides by context whether it is one
+    pub keyword: Option<TokenType>,
+    // an identifier written with unicode escapes
+    pub escaped: bool,
+    // the interned name of an identifier
+    pub atom: Option<Atom>,
+    // a line terminator (also one inside a multi-line comment) comes before this token,
+    // the parser needs it for automatic semicolon insertion and restricted productions
+    pub newline_before: bool,
+    // exact value of a string literal or cooked template piece with escapes,
+    // `value` is its lossy text. without escapes it is just `value`, see `cooked`
+    pub string: Option<JsString>,
+    // a string literal with legacy octal escapes like \07 or the \8 \9 escapes,
+    // which strict mode code doesn't allow
+    pub octal_escape: bool,
+    // raw source text of a template piece, `value` holds the cooked text
+    pub raw: Option<Cow<'a, str>>,
+    // an invalid escape inside a template piece, the cooked value is undefined then.
+    // it is only an error for untagged templates, so the parser decides
+    pub escape_error: Option<LexerError>,
+    // flags of a regexp literal, `value` holds the pattern body
+    pub regex_flags: Option<&'a str>,
+    // arbitrary-precision value of a bigint literal
+    pub bigint: Option<BigInt>
 }
 
-impl Token {
-    fn is_ident(&self) -> bool {
-        match self.category {
-            TokenType::Identifier => {
-                return true;
-            }
-            _ => {
-                return false;
-            }
+impl<'a> Token<'a> {
+    // an identifier spelling the contextual keyword tp, escapes never make a keyword
+    pub fn is_contextual(&self, tp: TokenType) -> bool {
+        self.category == TokenType::Identifier && self.keyword == Some(tp) && !self.escaped
+    }
+    // an identifier or a reserved word, what may follow a `.` as a property name
+    pub fn is_identifier_name(&self) -> bool {
+        self.category == TokenType::Identifier || try_keyword(&self.value) == self.category
+    }
+    // like `\u0069f`, it can be neither the keyword nor an identifier reference
+    pub fn is_escaped_reserved_word(&self) -> bool {
+        self.escaped && try_keyword(&self.value) != TokenType::Identifier
+    }
+    // the interned name of an identifier, or of a keyword used as a property name
+    pub fn name(&self) -> Atom {
+        match self.atom {
+            Some(atom) => atom,
+            None => Atom::from(&*self.value)
+        }
+    }
+    // the value of a string literal or template piece, None for a template piece
+    // with an invalid escape
+    pub fn cooked(&self) -> Option<JsString> {
+        if self.escape_error.is_some() {
+            return None;
+        }
+        match &self.string {
+            Some(st) => Some(st.clone()),
+            None => Some(JsString::from(&*self.value))
         }
     }
 }
 
-pub fn is_numeric_token (tp: TokenType) -> bool {
-    match tp {
-        TokenType::NUMERIC_LITERAL_BINARY | TokenType::NUMERIC_LITERAL_DECIMAL |
-        TokenType::NUMERIC_LITERAL_OCTAL | TokenType::NUMERIC_LITERAL_HEX => true,
-        _ => false
+pub fn is_comment_token (tp: TokenType) -> bool {
+    matches!(tp, TokenType::SINGLE_LINE_COMMENT | TokenType::MULTI_LINE_COMMENT |
+        TokenType::HASHBANG_COMMENT)
+}
+
+// without a parser, guess the goal symbol from the token before a `/`.
+// a regexp can't follow something that ends an expression, like an identifier or `)`
+pub fn regexp_allowed_after (prev: Option<TokenType>) -> bool {
+    match prev {
+        None => true,
+        Some(tp) => match tp {
+            TokenType::Identifier | TokenType::THIS | TokenType::SUPER |
+            TokenType::RPAREN | TokenType::RBRACK | TokenType::RBRACE |
+            TokenType::INC | TokenType::DEC |
+            TokenType::TRUE_LITERAL | TokenType::FALSE_LITERAL | TokenType::NULL_LITERAL |
+            TokenType::STRING_LITERAL | TokenType::REGEXP_LITERAL | TokenType::BIGINT_LITERAL |
+            TokenType::TEMPLATE_NO_SUBSTITUTION | TokenType::TEMPLATE_TAIL => false,
+            tp if is_numeric_token(tp) => false,
+            _ => true
+        }
     }
 }
 
+pub fn is_numeric_token (tp: TokenType) -> bool {
+    matches!(tp, TokenType::NUMERIC_LITERAL_BINARY | TokenType::NUMERIC_LITERAL_DECIMAL |
+        TokenType::NUMERIC_LITERAL_OCTAL | TokenType::NUMERIC_LITERAL_HEX |
+        TokenType::NUMERIC_LITERAL | TokenType::LEGACY_OCTAL_LITERAL |
+        TokenType::NON_OCTAL_DECIMAL_LITERAL)
+}
+
 pub fn is_alphabetic (s: char) -> bool {
-    return s.is_alphabetic(); 
+    s.is_alphabetic()
 }
 
-pub fn is_numeric (s: &str) -> bool {
-    let st = s.to_string();
-    let re = Regex::new(Number_Regex);
-    if let Ok(reg) = re {
-        return reg.is_match(&st);
-    } else {
-        false
+// all punctuators, longest first so the first prefix match is the longest one
+pub const PUNCTUATORS: &[(&str, TokenType)] = &[
+    (">>>=", TokenType::SHR_ASSIGN),
+    ("...", TokenType::ELLIPSIS),
+    ("===", TokenType::EQ_STRICT),
+    ("!==", TokenType::NE_STRICT),
+    ("**=", TokenType::EXP_ASSIGN),
+    ("<<=", TokenType::SHL_ASSIGN),
+    (">>=", TokenType::SAR_ASSIGN),
+    (">>>", TokenType::SHR),
+    ("&&=", TokenType::AND_ASSIGN),
+    ("||=", TokenType::OR_ASSIGN),
+    ("??=", TokenType::NULLISH_ASSIGN),
+    ("?.", TokenType::OPTIONAL_CHAIN),
+    ("??", TokenType::NULLISH),
+    ("=>", TokenType::ARROW),
+    ("==", TokenType::EQ),
+    ("!=", TokenType::NE),
+    ("<=", TokenType::LTE),
+    (">=", TokenType::GTE),
+    ("++", TokenType::INC),
+    ("--", TokenType::DEC),
+    ("&&", TokenType::AND),
+    ("||", TokenType::OR),
+    ("**", TokenType::EXP),
+    ("<<", TokenType::SHL),
+    (">>", TokenType::SAR),
+    ("+=", TokenType::ADD_ASSIGN),
+    ("-=", TokenType::SUB_ASSIGN),
+    ("*=", TokenType::MUL_ASSIGN),
+    ("/=", TokenType::DIV_ASSIGN),
+    ("%=", TokenType::MOD_ASSIGN),
+    ("&=", TokenType::BIT_AND_ASSIGN),
+    ("|=", TokenType::BIT_OR_ASSIGN),
+    ("^=", TokenType::BIT_XOR_ASSIGN),
+    ("{", TokenType::LBRACE),
+    ("}", TokenType::RBRACE),
+    ("(", TokenType::LPAREN),
+    (")", TokenType::RPAREN),
+    ("[", TokenType::LBRACK),
+    ("]", TokenType::RBRACK),
+    (".", TokenType::PERIOD),
+    (";", TokenType::SEMICOLON),
+    (",", TokenType::COMMA),
+    ("<", TokenType::LT),
+    (">", TokenType::GT),
+    ("+", TokenType::ADD),
+    ("-", TokenType::SUB),
+    ("*", TokenType::MUL),
+    ("/", TokenType::DIV),
+    ("%", TokenType::MOD),
+    ("&", TokenType::BIT_AND),
+    ("|", TokenType::BIT_OR),
+    ("^", TokenType::BIT_XOR),
+    ("!", TokenType::Bang),
+    ("~", TokenType::BIT_NOT),
+    ("?", TokenType::QUESTION),
+    (":", TokenType::COLON),
+    ("=", TokenType::ASSIGN),
+];
+
+
+// lookup tables for ASCII, indexed by byte
+pub static ASCII_IDENTIFIER_START: [bool; 128] = {
+    let mut table = [false; 128];
+    let mut i = 0;
+    while i < 128 {
+        let b = i as u8;
+        table[i] = b.is_ascii_alphabetic() || b == b'_' || b == b'$';
+        i = i + 1;
+    }
+    table
+};
+pub static ASCII_IDENTIFIER_CONTINUE: [bool; 128] = {
+    let mut table = [false; 128];
+    let mut i = 0;
+    while i < 128 {
+        let b = i as u8;
+        table[i] = b.is_ascii_alphanumeric() || b == b'_' || b == b'$';
+        i = i + 1;
+    }
+    table
+};
+pub static ASCII_PUNCTUATOR_START: [bool; 128] = {
+    let mut table = [false; 128];
+    let mut i = 0;
+    while i < PUNCTUATORS.len() {
+        table[PUNCTUATORS[i].0.as_bytes()[0] as usize] = true;
+        i = i + 1;
     }
+    table
+};
+
+pub fn is_punctuator_start (s: char) -> bool {
+    s.is_ascii() && ASCII_PUNCTUATOR_START[s as usize]
 }
-pub fn is_punctuator (s: &str) -> bool {
-    let st = s.to_string();
-    let re = Regex::new(Punctuators_Regex);
-    if let Ok(reg) = re {
-        return reg.is_match(&st);
-    } else {
-        false
+// longest match against the punctuator table,
+// returns the matched length in bytes and its token type
+pub fn match_punctuator (s: &[u8]) -> Option<(usize, TokenType)> {
+    let first = *s.first()?;
+    for (p, tp) in PUNCTUATORS {
+        let p = p.as_bytes();
+        if p[0] == first && s.starts_with(p) {
+            // `a?.5:0` is a conditional, `?.` must not be followed by a decimal digit
+            if *tp == TokenType::OPTIONAL_CHAIN && s.get(p.len()).is_some_and(|b| b.is_ascii_digit()) {
+                continue;
+            }
+            return Some((p.len(), *tp));
+        }
     }
+    None
 }
-pub fn get_punctuator_type (s: &str) -> TokenType {
-    match s {
-        "+" => TokenType::ADD,
-        "-" => TokenType::SUB,
-        "=" => TokenType::ASSIGN,
-        "(" => TokenType::LPAREN,
-        ")" => TokenType::RPAREN,
-        _ => TokenType::UNHANDLED,
+pub fn is_line_terminator (s: char) -> bool {
+    s == LF || s == CR || s == LS || s == PS
+}
+// each flag at most once, and `u` and `v` can't be combined
+pub fn valid_regexp_flags (flags: &str) -> bool {
+    let mut seen = 0u8;
+    for c in flags.chars() {
+        let bit = match "dgimsuvy".find(c) {
+            Some(i) => 1 << i,
+            None => return false
+        };
+        if seen & bit != 0 {
+            return false;
+        }
+        seen |= bit;
     }
+    !flags.contains('u') || !flags.contains('v')
 }
 pub fn is_string_literal_start (s: char) -> bool {
-    return  s == '\'' || s == '"';
+    s == '\'' || s == '"'
 }
 
 pub fn is_identifier_start (s: char) -> bool {
     if s.is_ascii() {
-        return s == '_' || s == '$' || s.is_ascii_alphabetic()
+        ASCII_IDENTIFIER_START[s as usize]
     } else {
-        return UnicodeXID::is_xid_start(s);
+        UnicodeXID::is_xid_start(s)
     }
 }
 pub fn is_identifier_continue(c: char) -> bool {
     if c.is_ascii() {
-        c == '$' || c == '_' || c.is_ascii_alphanumeric()
+        ASCII_IDENTIFIER_CONTINUE[c as usize]
     } else {
         UnicodeXID::is_xid_continue(c) || c == ZWNJ || c == ZWJ
     }
 }
+pub fn is_ascii_identifier_continue(b: u8) -> bool {
+    b < 128 && ASCII_IDENTIFIER_CONTINUE[b as usize]
+}
 
 pub fn is_unicode_seq_start (s: char) -> bool {
-    return s == '\\';
+    s == '\\'
 }
 // an unicode sequence identifier
 // let \u0061 = 'foobar'; let \u{0061} = 'foobar';let \u{0061}name = 'foobar'
 // read 4 chars next '\u' or '\u{' as a decimal number, then translate them to a char and check its validity
 
-pub fn hex_char_to_digit (s: char) -> Result<u32, LexerError> {
+pub fn hex_char_to_digit (s: char) -> Result<u32, LexerErrorKind> {
     match s {
         c @ '0'..='9' => Ok(c as u32 - '0' as u32),
         c @ 'a'..='f' => Ok(10 + (c as u32 - 'a' as u32)),
         c @ 'A'..='F' => Ok(10 + (c as u32 - 'A' as u32)),
-        _ => Err(LexerError::InvalidHexNumber)
+        _ => Err(LexerErrorKind::InvalidHexNumber)
     }
 }
 
-pub fn digit_to_char (s: u32) -> Result<char, LexerError> {
+pub fn digit_to_char (s: u32) -> Result<char, LexerErrorKind> {
     if s > 0x10FFFF {
-        return Err(LexerError::UnicodeOverfow);
+        return Err(LexerErrorKind::UnicodeOverfow);
     }
-    if s >= 0xd800 && s <= 0xdfff {
-        return Err(LexerError::UnicodeSurrogateCodePoint);
+    if (0xd800..=0xdfff).contains(&s) {
+        return Err(LexerErrorKind::UnicodeSurrogateCodePoint);
     }
-    char::try_from(s).map_err(|e| LexerError::InvalidUnicodeSequence)
+    char::try_from(s).map_err(|_e| LexerErrorKind::InvalidUnicodeSequence)
 }
+// reserved words, they are never identifiers
 pub fn try_keyword (s: &str) -> TokenType {
     match s {
         "break" => TokenType::BREAK,
@@ -209,9 +452,12 @@ pub fn try_keyword (s: &str) -> TokenType {
         "case" => TokenType::CASE,
         "continue" => TokenType::CONTINUE,
         "for" => TokenType::FOR,
-        "let" => TokenType::LET,
         "var" => TokenType::VAR,
+        "const" => TokenType::CONST,
         "function" => TokenType::FUNCTION,
+        "class" => TokenType::CLASS,
+        "extends" => TokenType::EXTENDS,
+        "super" => TokenType::SUPER,
         "this" => TokenType::THIS,
         "catch" => TokenType::CATCH,
         "try" => TokenType::TRY,
@@ -227,15 +473,44 @@ pub fn try_keyword (s: &str) -> TokenType {
         "delete" => TokenType::DELETE,
         "typeof" => TokenType::TYPEOF,
         "instanceof" => TokenType::INSTANCEOF,
+        "void" => TokenType::VOID,
         "new" => TokenType::NEW,
         "in" => TokenType::IN,
+        "import" => TokenType::IMPORT,
+        "export" => TokenType::EXPORT,
+        "debugger" => TokenType::DEBUGGER,
+        "enum" => TokenType::ENUM,
         "false" => TokenType::FALSE_LITERAL,
         "true" => TokenType::TRUE_LITERAL,
         "null" => TokenType::NULL_LITERAL,
-        "undefined" => TokenType::UNDEFINED_LITERAL,
         _ => TokenType::Identifier
     }
 }
+// words that are identifiers in some contexts, `let` and `static` are also strict mode reserved
+pub fn try_contextual_keyword (s: &str) -> Option<TokenType> {
+    match s {
+        "let" => Some(TokenType::LET),
+        "static" => Some(TokenType::STATIC),
+        "async" => Some(TokenType::ASYNC),
+        "await" => Some(TokenType::AWAIT),
+        "yield" => Some(TokenType::YIELD),
+        "of" => Some(TokenType::OF),
+        "get" => Some(TokenType::GET),
+        "set" => Some(TokenType::SET),
+        "implements" => Some(TokenType::IMPLEMENTS),
+        "interface" => Some(TokenType::INTERFACE),
+        "package" => Some(TokenType::PACKAGE),
+        "private" => Some(TokenType::PRIVATE),
+        "protected" => Some(TokenType::PROTECTED),
+        "public" => Some(TokenType::PUBLIC),
+        _ => None
+    }
+}
+pub fn is_strict_mode_reserved (tp: TokenType) -> bool {
+    matches!(tp, TokenType::LET | TokenType::STATIC | TokenType::YIELD |
+        TokenType::IMPLEMENTS | TokenType::INTERFACE | TokenType::PACKAGE |
+        TokenType::PRIVATE | TokenType::PROTECTED | TokenType::PUBLIC)
+}
 
 pub enum TokenMatcher {
     Single(TokenType),
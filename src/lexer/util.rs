@@ -1,3 +1,4 @@
+use num_bigint::{BigInt, BigUint};
 use crate::lexer::token::{TokenType};
 // in javascript, number is a double type value
 // an IEEE 754 64-bit double has 52 bits of mantissa,
@@ -5,87 +6,59 @@ use crate::lexer::token::{TokenType};
 // octal: 377777777777777777
 // hex: 1fffffffffffff
 // binary: 11111111111111111111111111111111111111111111111111111
-// for the purpose of simplification, just store arbitrarily number in f64
+// past that, adding digit by digit in f64 rounds at every step and can be off
+// by one ulp, so the digits are accumulated exactly and rounded once at the end
 
-pub fn parse_binary_number(s: &str) -> Result<f64, String> {
-    let bs = s.as_bytes();
-    let mut result: f64 = 0.0;
-    for c in bs {
-        match c {
-            b'0'..=b'1' => {
-                let ct = c - b'0';
-                result = result * 2.0 + ct as f64;
-            }
-            _ => {
-                return Err("invalid number".to_string());
+// digits of a non-decimal integer literal, separators already removed
+fn parse_radix(s: &str, radix: u32) -> Result<f64, String> {
+    let mut result: u64 = 0;
+    for c in s.chars() {
+        let digit = c.to_digit(radix).ok_or("invalid number".to_string())?;
+        match result.checked_mul(radix as u64).and_then(|r| r.checked_add(digit as u64)) {
+            Some(r) => result = r,
+            // more than 64 bits, the exact decimal text goes through the correctly rounded std parser
+            None => {
+                let big = BigUint::parse_bytes(s.as_bytes(), radix).ok_or("invalid number".to_string())?;
+                return parse_decimal(&big.to_string());
             }
         }
     }
-    Ok(result)
+    // u64 to f64 rounds to nearest, ties to even
+    Ok(result as f64)
 }
-pub fn parse_decimal_int(s: &str) -> Result<f64, String> {
-    let bs = s.as_bytes();
-    let mut result: f64 = 0.0;
-    for c in bs {
-        match c {
-            b'0'..=b'9' => {
-                let ct = c - b'0';
-                result = result * 10.0 + ct as f64;
-            }
-            _ => {
-                return Err("invalid number".to_string());
-            }
-        }
-    }
-    Ok(result)
+pub fn parse_binary_number(s: &str) -> Result<f64, String> {
+    parse_radix(s, 2)
+}
+// DecimalLiteral text with fraction and exponent, separators already removed.
+// the std parser is correctly rounded, which is what ECMAScript requires
+pub fn parse_decimal(s: &str) -> Result<f64, String> {
+    s.parse::<f64>().map_err(|_| "invalid number".to_string())
 }
 pub fn parse_octal_number(s: &str) -> Result<f64, String> {
-    let bs = s.as_bytes();
-    let mut result: f64 = 0.0;
-    for c in bs {
-        match c {
-            b'0'..=b'7' => {
-                let ct = c - b'0';
-                result = result * 8.0 + ct as f64;
-            }
-            _ => {
-                return Err("invalid number".to_string());
-            }
-        }
-    }
-    Ok(result)
+    parse_radix(s, 8)
 }
 pub fn parse_hex_number(s: &str) -> Result<f64, String> {
-    let bs = s.as_bytes();
-    let mut result: f64 = 0.0;
-    for c in bs {
-        match c {
-            b'0'..=b'9' => {
-                let ct = c - b'0';
-                result = result * 16.0 + ct as f64;
-            }
-            b'a'..=b'f' => {
-                let ct = c - b'a' + 10;
-                result = result * 16.0 + ct as f64;
-            }
-            b'A'..=b'F' => {
-                let ct = c - b'A' + 10;
-                result = result * 16.0 + ct as f64;
-            }
-            _ => {
-                return Err("invalid number".to_string());
-            }
-        }
-    }
-    Ok(result)
+    parse_radix(s, 16)
 }
 
 pub fn parse_numeric(s: &str, tp: &TokenType) -> Result<f64, String> {
     match tp {
         TokenType::NUMERIC_LITERAL_BINARY => parse_binary_number(s),
-        TokenType::NUMERIC_LITERAL_DECIMAL => parse_decimal_int(s),
-        TokenType::NUMERIC_LITERAL_OCTAL => parse_octal_number(s),
+        TokenType::NUMERIC_LITERAL_DECIMAL | TokenType::NUMERIC_LITERAL |
+        TokenType::NON_OCTAL_DECIMAL_LITERAL => parse_decimal(s),
+        TokenType::NUMERIC_LITERAL_OCTAL | TokenType::LEGACY_OCTAL_LITERAL => parse_octal_number(s),
         TokenType::NUMERIC_LITERAL_HEX => parse_hex_number(s),
         _ => Err("invalid number".to_string())
     }
-}
\ No newline at end of file
+}
+// bigint literals keep every digit, the radix comes from the literal's prefix
+pub fn parse_bigint(s: &str, tp: &TokenType) -> Result<BigInt, String> {
+    let radix = match tp {
+        TokenType::NUMERIC_LITERAL_BINARY => 2,
+        TokenType::NUMERIC_LITERAL_OCTAL => 8,
+        TokenType::NUMERIC_LITERAL_DECIMAL => 10,
+        TokenType::NUMERIC_LITERAL_HEX => 16,
+        _ => return Err("invalid number".to_string())
+    };
+    BigInt::parse_bytes(s.as_bytes(), radix).ok_or("invalid number".to_string())
+}
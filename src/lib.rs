@@ -0,0 +1,6 @@
+pub mod lexer;
+pub mod input;
+pub mod parser;
+pub mod ast;
+pub mod js_string;
+pub mod atom;
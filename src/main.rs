@@ -1,20 +1,147 @@
 use std::env;
 use std::fs;
+use std::io::{self, Write};
+use std::process;
 
-mod lexer;
-mod input;
-mod parser;
-mod ast;
+use metal::lexer;
+use metal::parser;
+use metal::lexer::lexer::{Lexer, LexGoal};
+use metal::lexer::token::{Token, regexp_allowed_after};
+use metal::input::Position;
+
+// metal file.js
+// metal tokens [--json] file.js
 fn main() {
-    let arg = env::args().nth(1).expect("require a js file");
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.first().map(|a| a.as_str()) == Some("tokens") {
+        let json = args.iter().any(|a| a == "--json");
+        let file = args.iter().skip(1).find(|a| !a.starts_with("--")).expect("require a js file");
+        let content = fs::read_to_string(file).expect("read file failed");
+        match print_tokens(&content, json) {
+            Ok(true) => {}
+            Ok(false) => process::exit(1),
+            // `metal tokens f.js | head` stops reading early, that is not an error
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => {}
+            Err(e) => {
+                eprintln!("{}", e);
+                process::exit(1);
+            }
+        }
+        return;
+    }
+    let arg = args.first().expect("require a js file");
     let content = fs::read_to_string(arg).expect("read file failed");
-    let mut lexer_ins = lexer::lexer::Lexer::new(&content);
+    let lexer_ins = Lexer::new(&content);
     let mut pas = parser::Parser::new(lexer_ins);
-    pas.parse();
-    // use advance method to get next token
-    // for _ in 0..20 {
-    //     let t = lexer_ins.advance();
-    //     println!("{:?}", t);
-    // }
+    match pas.parse() {
+        Ok(program) => println!("{:#?}", program),
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    }
+}
+
+// print every token, one per line. there's no parser to pick the goal symbol,
+// so a `/` is read as a regexp or a division by looking at the token before it.
+// false when the source doesn't lex
+fn print_tokens(content: &str, json: bool) -> io::Result<bool> {
+    let mut out = io::BufWriter::new(io::stdout().lock());
+    let mut lexer_ins = Lexer::new(content);
+    lexer_ins.keep_comments(true);
+    loop {
+        match lexer_ins.next() {
+            Some(Ok(t)) => {
+                if json {
+                    writeln!(out, "{}", token_json(&t))?;
+                } else {
+                    writeln!(out, "{}", token_text(&t))?;
+                }
+                if !lexer::token::is_comment_token(t.category) {
+                    if regexp_allowed_after(Some(t.category)) {
+                        lexer_ins.set_goal(LexGoal::InputElementRegExp);
+                    } else {
+                        lexer_ins.set_goal(LexGoal::InputElementDiv);
+                    }
+                }
+            }
+            Some(Err(e)) => {
+                out.flush()?;
+                eprintln!("{}", e);
+                return Ok(false);
+            }
+            None => {
+                out.flush()?;
+                return Ok(true);
+            }
+        }
+    }
+}
+
+// 1:5-1:8 utf16 5-8 byte 5-8 Identifier "foo", with the flags of a regexp
+// and the raw text of a template piece after the value
+fn token_text(t: &Token) -> String {
+    let mut text = format!(
+        "{}:{}-{}:{} utf16 {}-{} byte {}-{} {:?} {:?}",
+        t.span.start.line, t.span.start.column,
+        t.span.end.line, t.span.end.column,
+        t.span.start.column_utf16, t.span.end.column_utf16,
+        t.span.start.column_byte, t.span.end.column_byte,
+        t.category, t.value
+    );
+    if let Some(flags) = t.regex_flags {
+        text.push_str(&format!(" flags {:?}", flags));
+    }
+    if let Some(raw) = &t.raw {
+        text.push_str(&format!(" raw {:?}", raw));
+    }
+    text
+}
+
+// {"type":"Identifier","value":"foo","start":{...},"end":{...},"newline_before":false},
+// "flags" and "raw" are only there for regexps and template pieces
+fn token_json(t: &Token) -> String {
+    let mut extra = String::new();
+    if let Some(flags) = t.regex_flags {
+        extra.push_str(&format!(",\"flags\":{}", json_string(flags)));
+    }
+    if let Some(raw) = &t.raw {
+        extra.push_str(&format!(",\"raw\":{}", json_string(raw)));
+    }
+    format!(
+        "{{\"type\":\"{:?}\",\"value\":{}{},\"start\":{},\"end\":{},\"newline_before\":{}}}",
+        t.category,
+        json_string(&t.value),
+        extra,
+        position_json(&t.span.start),
+        position_json(&t.span.end),
+        t.newline_before
+    )
+}
+
+fn position_json(p: &Position) -> String {
+    format!(
+        "{{\"offset\":{},\"line\":{},\"column\":{},\"column_utf16\":{},\"column_byte\":{}}}",
+        p.offset, p.line, p.column, p.column_utf16, p.column_byte
+    )
+}
 
+fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            // JSON allows LS and PS in strings but plenty of tools choke on them
+            c if (c as u32) < 0x20 || c == '\u{2028}' || c == '\u{2029}' => {
+                out.push_str(&format!("\\u{:04x}", c as u32));
+            }
+            c => out.push(c)
+        }
+    }
+    out.push('"');
+    out
 }
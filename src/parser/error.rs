@@ -1,7 +1,153 @@
-use crate::lexer::error::{LexerError};
+use std::fmt;
+use crate::input::Span;
+use crate::lexer::error::{LexerError, render};
+
 #[derive(Debug)]
-pub enum ParseError {
-    LexerError(LexerError),
+pub enum ParseErrorKind {
+    // boxed, the lexer error carries a span and source line and would make every result large
+    LexerError(Box<LexerError>),
     AlreadyDeclared(String),
-    UnexpectedToken(String)
-}
\ No newline at end of file
+    UnexpectedToken(String),
+    UnexpectedEnd,
+    // `\u0069f` where an identifier or the keyword is expected
+    EscapedReservedWord(String),
+    StrictModeReservedWord(String),
+    // legacy octal and NonOctalDecimal literals like 010 and 089
+    OctalLiteralInStrictMode(String),
+    // "\07" and "\8" in strict mode code
+    OctalEscapeInStrictMode(String),
+    // like `1 = a` or `a() += 1`
+    InvalidAssignmentTarget,
+    // `eval` and `arguments` can't be assigned in strict mode
+    StrictModeAssignment(String),
+    // `delete x` in strict mode code
+    DeleteIdentifierInStrictMode(String),
+    // `a ?? b || c` needs parentheses around one of the operators
+    MixedNullishCoalescing,
+    // `-a ** b` or `await a ** b` needs parentheses, it could mean `(-a) ** b` or `-(a ** b)`
+    UnparenthesizedUnaryExponent,
+    // a?.b`c`
+    TaggedTemplateInOptionalChain,
+    // `return` outside of a function
+    IllegalReturn,
+    // `break` outside of a loop or switch
+    IllegalBreak,
+    // `continue` outside of a loop, or to a label that is not on a loop
+    IllegalContinue,
+    UndefinedLabel(String),
+    // `a: a: x`
+    DuplicateLabel(String),
+    // more than one `default:` in a switch
+    MultipleDefaults,
+    WithInStrictMode,
+    // `throw` and its expression must be on the same line
+    NewlineAfterThrow,
+    // `try {}` alone
+    MissingCatchOrFinally,
+    // `for (let a = 1 in b)`
+    ForInOfInitializer,
+    // `for (async of x)`
+    ForOfAsync,
+    // `const a;`
+    MissingConstInitializer,
+    // `let [a];`
+    MissingDestructuringInitializer,
+    // `let let = 1`
+    LetInLexicalDeclaration,
+    // `function f(a, a) { "use strict" }`, or any repeat in an arrow or non-simple list
+    DuplicateParameter(String),
+    // `function f(a = 1) { "use strict" }`
+    UseStrictWithNonSimpleParameters,
+    // `get a(b) {}` or `set a() {}`
+    InvalidAccessorParameters,
+    // `{ __proto__: a, "__proto__": b }`
+    DuplicateProto,
+    // `{ a = 1 }` outside of a pattern
+    ShorthandInitializer,
+    // `(a.b) => c` or `(1) => c`, parameters only bind names
+    InvalidArrowParameter,
+    // `super` outside of a method, or `super(...)`
+    UnexpectedSuper,
+    // `new.target` outside of a function body
+    NewTargetOutsideFunction,
+    // `new a?.b()`
+    OptionalChainInNew
+}
+
+impl ParseErrorKind {
+    pub fn message(&self) -> String {
+        match self {
+            Self::LexerError(e) => e.message(),
+            Self::AlreadyDeclared(n) => format!("`{}` has already been declared", n),
+            Self::UnexpectedToken(t) => format!("unexpected `{}`", t),
+            Self::UnexpectedEnd => "unexpected end of input".to_string(),
+            Self::EscapedReservedWord(w) => format!("keyword `{}` must not contain escapes", w),
+            Self::StrictModeReservedWord(w) => format!("`{}` is a reserved word in strict mode", w),
+            Self::OctalLiteralInStrictMode(n) => format!("octal literal `{}` is not allowed in strict mode", n),
+            Self::OctalEscapeInStrictMode(s) => format!("octal escape in {:?} is not allowed in strict mode", s),
+            Self::InvalidAssignmentTarget => "invalid assignment target".to_string(),
+            Self::StrictModeAssignment(n) => format!("`{}` can't be assigned or bound in strict mode", n),
+            Self::DeleteIdentifierInStrictMode(n) => format!("`delete {}` is not allowed in strict mode", n),
+            Self::MixedNullishCoalescing => "`??` can't be mixed with `&&` or `||` without parentheses".to_string(),
+            Self::UnparenthesizedUnaryExponent => "the left side of `**` must be parenthesized".to_string(),
+            Self::TaggedTemplateInOptionalChain => "tagged template in an optional chain".to_string(),
+            Self::IllegalReturn => "`return` outside of a function".to_string(),
+            Self::IllegalBreak => "`break` outside of a loop or switch".to_string(),
+            Self::IllegalContinue => "`continue` outside of a loop".to_string(),
+            Self::UndefinedLabel(l) => format!("undefined label `{}`", l),
+            Self::DuplicateLabel(l) => format!("label `{}` has already been declared", l),
+            Self::MultipleDefaults => "more than one default clause in switch".to_string(),
+            Self::WithInStrictMode => "`with` is not allowed in strict mode".to_string(),
+            Self::NewlineAfterThrow => "line break after `throw`".to_string(),
+            Self::MissingCatchOrFinally => "`try` without `catch` or `finally`".to_string(),
+            Self::ForInOfInitializer => "for-in and for-of declarations can't have an initializer".to_string(),
+            Self::ForOfAsync => "the left side of for-of can't be `async`".to_string(),
+            Self::MissingConstInitializer => "missing initializer in const declaration".to_string(),
+            Self::MissingDestructuringInitializer => "missing initializer in destructuring declaration".to_string(),
+            Self::LetInLexicalDeclaration => "`let` can't be a lexically bound name".to_string(),
+            Self::DuplicateParameter(n) => format!("duplicate parameter `{}`", n),
+            Self::UseStrictWithNonSimpleParameters => "\"use strict\" in a function with non-simple parameters".to_string(),
+            Self::InvalidAccessorParameters => "a getter takes no parameters and a setter exactly one".to_string(),
+            Self::DuplicateProto => "duplicate `__proto__` property".to_string(),
+            Self::ShorthandInitializer => "shorthand property initializer outside of a pattern".to_string(),
+            Self::InvalidArrowParameter => "invalid arrow function parameter".to_string(),
+            Self::UnexpectedSuper => "`super` is only valid as `super.x` or `super[x]` in a method".to_string(),
+            Self::NewTargetOutsideFunction => "`new.target` outside of a function".to_string(),
+            Self::OptionalChainInNew => "optional chain in a `new` expression".to_string()
+        }
+    }
+}
+
+// a parse error at a token, the source line is kept for rendering like LexerError.
+// the span is boxed to keep results small
+#[derive(Debug)]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    pub span: Box<Span>,
+    pub source_line: String
+}
+
+impl ParseError {
+    pub fn message(&self) -> String {
+        self.kind.message()
+    }
+}
+
+impl From<LexerError> for ParseError {
+    fn from(e: LexerError) -> Self {
+        Self {
+            span: Box::new(e.span),
+            source_line: e.source_line.clone(),
+            kind: ParseErrorKind::LexerError(Box::new(e))
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ParseErrorKind::LexerError(e) => e.fmt(f),
+            kind => render(f, &kind.message(), &self.span, &self.source_line)
+        }
+    }
+}
@@ -0,0 +1,1023 @@
+use crate::atom::Atom;
+use crate::lexer::token::{Token, TokenType, TokenMatcher};
+use crate::ast::expression::*;
+use crate::ast::variable::Identifier;
+use super::{Parser, Cover};
+use super::error::{ParseError, ParseErrorKind};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Infix {
+    Binary(BinaryOpt),
+    Logical(LogicalOp)
+}
+
+// binding power of a binary or logical operator, a higher one binds tighter.
+// `??` shares its level with `||` but the two can't be mixed without parentheses
+fn infix_operator(tp: TokenType) -> Option<(u8, Infix)> {
+    let op = match tp {
+        TokenType::NULLISH => (1, Infix::Logical(LogicalOp::Nullish)),
+        TokenType::OR => (1, Infix::Logical(LogicalOp::Or)),
+        TokenType::AND => (2, Infix::Logical(LogicalOp::And)),
+        TokenType::BIT_OR => (3, Infix::Binary(BinaryOpt::BitOr)),
+        TokenType::BIT_XOR => (4, Infix::Binary(BinaryOpt::BitXor)),
+        TokenType::BIT_AND => (5, Infix::Binary(BinaryOpt::BitAnd)),
+        TokenType::EQ => (6, Infix::Binary(BinaryOpt::Eq)),
+        TokenType::NE => (6, Infix::Binary(BinaryOpt::Ne)),
+        TokenType::EQ_STRICT => (6, Infix::Binary(BinaryOpt::EqStrict)),
+        TokenType::NE_STRICT => (6, Infix::Binary(BinaryOpt::NeStrict)),
+        TokenType::LT => (7, Infix::Binary(BinaryOpt::Lt)),
+        TokenType::GT => (7, Infix::Binary(BinaryOpt::Gt)),
+        TokenType::LTE => (7, Infix::Binary(BinaryOpt::Lte)),
+        TokenType::GTE => (7, Infix::Binary(BinaryOpt::Gte)),
+        TokenType::INSTANCEOF => (7, Infix::Binary(BinaryOpt::InstanceOf)),
+        TokenType::IN => (7, Infix::Binary(BinaryOpt::In)),
+        TokenType::SHL => (8, Infix::Binary(BinaryOpt::Shl)),
+        TokenType::SAR => (8, Infix::Binary(BinaryOpt::Sar)),
+        TokenType::SHR => (8, Infix::Binary(BinaryOpt::Shr)),
+        TokenType::ADD => (9, Infix::Binary(BinaryOpt::Add)),
+        TokenType::SUB => (9, Infix::Binary(BinaryOpt::Sub)),
+        TokenType::MUL => (10, Infix::Binary(BinaryOpt::Mul)),
+        TokenType::DIV => (10, Infix::Binary(BinaryOpt::Div)),
+        TokenType::MOD => (10, Infix::Binary(BinaryOpt::Mod)),
+        // right associative
+        TokenType::EXP => (11, Infix::Binary(BinaryOpt::Exp)),
+        _ => return None
+    };
+    Some(op)
+}
+
+fn assign_operator(tp: TokenType) -> Option<AssignOp> {
+    let op = match tp {
+        TokenType::ASSIGN => AssignOp::Assign,
+        TokenType::ADD_ASSIGN => AssignOp::Add,
+        TokenType::SUB_ASSIGN => AssignOp::Sub,
+        TokenType::MUL_ASSIGN => AssignOp::Mul,
+        TokenType::DIV_ASSIGN => AssignOp::Div,
+        TokenType::MOD_ASSIGN => AssignOp::Mod,
+        TokenType::EXP_ASSIGN => AssignOp::Exp,
+        TokenType::SHL_ASSIGN => AssignOp::Shl,
+        TokenType::SAR_ASSIGN => AssignOp::Sar,
+        TokenType::SHR_ASSIGN => AssignOp::Shr,
+        TokenType::BIT_AND_ASSIGN => AssignOp::BitAnd,
+        TokenType::BIT_OR_ASSIGN => AssignOp::BitOr,
+        TokenType::BIT_XOR_ASSIGN => AssignOp::BitXor,
+        TokenType::AND_ASSIGN => AssignOp::And,
+        TokenType::OR_ASSIGN => AssignOp::Or,
+        TokenType::NULLISH_ASSIGN => AssignOp::Nullish,
+        _ => return None
+    };
+    Some(op)
+}
+
+fn unary_operator(tp: TokenType) -> Option<UnaryOp> {
+    let op = match tp {
+        TokenType::ADD => UnaryOp::Add,
+        TokenType::SUB => UnaryOp::Sub,
+        TokenType::Bang => UnaryOp::Bang,
+        TokenType::BIT_NOT => UnaryOp::BitNot,
+        TokenType::TYPEOF => UnaryOp::Typeof,
+        TokenType::VOID => UnaryOp::Void,
+        TokenType::DELETE => UnaryOp::Delete,
+        _ => return None
+    };
+    Some(op)
+}
+
+// a parenthesized list and the errors its elements leave for when it is not parameters
+pub(super) struct ParenList {
+    pub(super) elements: Vec<Expression>,
+    // `(a,)`, fine for parameters and arguments but not an expression
+    pub(super) trailing_comma: bool,
+    pub(super) cover: Cover
+}
+
+// an array or object literal not wrapped in parentheses, what may be read as a pattern
+pub(super) fn is_literal(e: &Expression) -> bool {
+    matches!(e, Expression::Array(_) | Expression::Object(_))
+}
+
+// a logical expression with one of ops, not wrapped in parentheses
+fn is_bare_logical(e: &Expression, ops: &[LogicalOp]) -> bool {
+    matches!(e, Expression::Logical(l) if ops.contains(&l.operator))
+}
+
+impl<'a> Parser<'a> {
+    // Expression: AssignmentExpression, with the comma operator
+    pub fn parse_expression(&mut self) -> Result<Expression, ParseError> {
+        let first = self.parse_assignment()?;
+        self.parse_sequence(first)
+    }
+    // the expressions after the first one of a comma list
+    pub(super) fn parse_sequence(&mut self, first: Expression) -> Result<Expression, ParseError> {
+        if !self.peek_is(TokenType::COMMA)? {
+            return Ok(first);
+        }
+        let mut expressions = vec![first];
+        while self.peek_is(TokenType::COMMA)? {
+            self.next()?;
+            expressions.push(self.parse_assignment()?);
+        }
+        Ok(Expression::Sequence(SequenceExpr { expressions }))
+    }
+    // right associative, `a = b = c` is `a = (b = c)`
+    pub fn parse_assignment(&mut self) -> Result<Expression, ParseError> {
+        let (e, cover) = self.with_cover(|p| p.parse_assignment_cover())?;
+        if let Some(err) = cover.expression {
+            return Err(err);
+        }
+        Ok(e)
+    }
+    // an element of an array or object literal, which may be read again as a pattern
+    // when the literal is. an element that is itself a literal leaves its errors
+    // in the cover for the enclosing one to decide
+    pub(super) fn parse_assignment_cover(&mut self) -> Result<Expression, ParseError> {
+        if self.in_generator && self.peek()?.is_some_and(|t| t.is_contextual(TokenType::YIELD)) {
+            return self.parse_yield();
+        }
+        if let Some(arrow) = self.parse_arrow_start()? {
+            return Ok(arrow);
+        }
+        let (left, cover) = self.with_cover(|p| p.parse_conditional())?;
+        let op = match self.peek()? {
+            Some(t) => assign_operator(t.category),
+            None => None
+        };
+        let op = match op {
+            Some(op) => op,
+            None => {
+                if is_literal(&left) {
+                    self.cover.merge(cover);
+                } else if let Some(err) = cover.expression {
+                    return Err(err);
+                }
+                return Ok(left);
+            }
+        };
+        let left = if op == AssignOp::Assign {
+            self.to_assignment_target(left, cover)?
+        } else {
+            if let Some(err) = cover.expression {
+                return Err(err);
+            }
+            self.to_simple_target(left)?
+        };
+        self.next()?;
+        let right = self.parse_assignment()?;
+        Ok(Expression::Assign(AssignExpr {
+            operator: op,
+            left: Box::new(left),
+            right: Box::new(right)
+        }))
+    }
+    fn parse_conditional(&mut self) -> Result<Expression, ParseError> {
+        let test = self.parse_binary(1)?;
+        if !self.peek_is(TokenType::QUESTION)? {
+            return Ok(test);
+        }
+        self.next()?;
+        let consequent = self.with_in(true, |p| p.parse_assignment())?;
+        self.next_check(TokenMatcher::from(TokenType::COLON))?;
+        let alternate = self.parse_assignment()?;
+        Ok(Expression::Conditional(ConditionalExpr {
+            test: Box::new(test),
+            consequent: Box::new(consequent),
+            alternate: Box::new(alternate)
+        }))
+    }
+    // precedence climbing, operators binding at least as tight as min_prec
+    fn parse_binary(&mut self, min_prec: u8) -> Result<Expression, ParseError> {
+        let mut left = self.parse_unary()?;
+        while let Some(t) = self.peek()? {
+            let (prec, op) = match infix_operator(t.category) {
+                Some(o) => o,
+                None => break
+            };
+            if prec < min_prec || (op == Infix::Binary(BinaryOpt::In) && !self.allow_in) {
+                break;
+            }
+            if op == Infix::Binary(BinaryOpt::Exp) && matches!(left, Expression::Unary(_) | Expression::Await(_)) {
+                return Err(self.error(ParseErrorKind::UnparenthesizedUnaryExponent));
+            }
+            self.next()?;
+            let right = if op == Infix::Binary(BinaryOpt::Exp) {
+                self.parse_binary(prec)?
+            } else {
+                self.parse_binary(prec + 1)?
+            };
+            left = match op {
+                Infix::Binary(op) => Expression::Bin(BinaryExp {
+                    operator: op,
+                    left: Box::new(left),
+                    right: Box::new(right)
+                }),
+                Infix::Logical(op) => {
+                    let other = if op == LogicalOp::Nullish {
+                        [LogicalOp::And, LogicalOp::Or]
+                    } else {
+                        [LogicalOp::Nullish, LogicalOp::Nullish]
+                    };
+                    if is_bare_logical(&left, &other) || is_bare_logical(&right, &other) {
+                        return Err(self.error(ParseErrorKind::MixedNullishCoalescing));
+                    }
+                    Expression::Logical(LogicalExpr {
+                        operator: op,
+                        left: Box::new(left),
+                        right: Box::new(right)
+                    })
+                }
+            };
+        }
+        Ok(left)
+    }
+    // unary operators, and prefix or postfix `++` and `--`
+    fn parse_unary(&mut self) -> Result<Expression, ParseError> {
+        // the token after a primary read ahead is not a prefix operator
+        if self.primary.is_some() {
+            let expr = self.parse_lhs()?;
+            return self.parse_postfix(expr);
+        }
+        let tp = match self.peek()? {
+            Some(t) => t.category,
+            None => return Err(self.error(ParseErrorKind::UnexpectedEnd))
+        };
+        if self.in_async && self.peek()?.is_some_and(|t| t.is_contextual(TokenType::AWAIT)) {
+            self.next()?;
+            let argument = self.parse_unary()?;
+            return Ok(Expression::Await(AwaitExpr { argument: Box::new(argument) }));
+        }
+        if let Some(op) = unary_operator(tp) {
+            self.next()?;
+            let argument = self.parse_unary()?;
+            if op == UnaryOp::Delete && self.strict {
+                if let Expression::Identifier(id) = argument.unparenthesized() {
+                    return Err(self.error(ParseErrorKind::DeleteIdentifierInStrictMode(id.value.to_string())));
+                }
+            }
+            return Ok(Expression::Unary(UnaryExpr {
+                operator: op,
+                argument: Box::new(argument)
+            }));
+        }
+        if tp == TokenType::INC || tp == TokenType::DEC {
+            self.next()?;
+            let argument = self.parse_unary()?;
+            self.check_simple_target(&argument)?;
+            return Ok(Expression::Update(UpdateExpr {
+                operator: if tp == TokenType::INC { UpdateOp::Inc } else { UpdateOp::Dec },
+                prefix: true,
+                argument: Box::new(argument)
+            }));
+        }
+        let expr = self.parse_lhs()?;
+        self.parse_postfix(expr)
+    }
+    fn parse_postfix(&mut self, expr: Expression) -> Result<Expression, ParseError> {
+        // no line break before a postfix operator, `a\n++b` is `a; ++b`
+        if self.newline_before_next()? {
+            return Ok(expr);
+        }
+        let postfix = match self.peek()? {
+            Some(t) if t.category == TokenType::INC => Some(UpdateOp::Inc),
+            Some(t) if t.category == TokenType::DEC => Some(UpdateOp::Dec),
+            _ => None
+        };
+        match postfix {
+            Some(op) => {
+                self.check_simple_target(&expr)?;
+                self.next()?;
+                Ok(Expression::Update(UpdateExpr {
+                    operator: op,
+                    prefix: false,
+                    argument: Box::new(expr)
+                }))
+            }
+            None => Ok(expr)
+        }
+    }
+    // LeftHandSideExpression: new, calls, member accesses and optional chains
+    fn parse_lhs(&mut self) -> Result<Expression, ParseError> {
+        let object = if let Some(primary) = self.primary.take() {
+            primary
+        } else if self.peek_is(TokenType::NEW)? {
+            self.parse_new()?
+        } else if self.peek_is(TokenType::SUPER)? {
+            self.parse_super()?
+        } else {
+            self.parse_primary()?
+        };
+        self.parse_call_tail(object, true)
+    }
+    // `new X(args)`, the arguments bind to the nearest `new`, so `new new X()()` works
+    fn parse_new(&mut self) -> Result<Expression, ParseError> {
+        self.next_check(TokenMatcher::from(TokenType::NEW))?;
+        if self.peek_is(TokenType::PERIOD)? {
+            return self.parse_new_target();
+        }
+        let callee = if self.peek_is(TokenType::NEW)? {
+            self.parse_new()?
+        } else if self.peek_is(TokenType::SUPER)? {
+            self.parse_super()?
+        } else {
+            self.parse_primary()?
+        };
+        let callee = self.parse_call_tail(callee, false)?;
+        // `new a?.b()`, the chain would have to end before the arguments
+        if self.peek_is(TokenType::OPTIONAL_CHAIN)? {
+            self.next()?;
+            return Err(self.error(ParseErrorKind::OptionalChainInNew));
+        }
+        let arguments = if self.peek_is(TokenType::LPAREN)? {
+            self.parse_arguments()?
+        } else {
+            vec![]
+        };
+        Ok(Expression::New(NewExpr {
+            callee: Box::new(callee),
+            arguments
+        }))
+    }
+    // `new.target` after the `new`, in a function body
+    fn parse_new_target(&mut self) -> Result<Expression, ParseError> {
+        self.next_check(TokenMatcher::from(TokenType::PERIOD))?;
+        let t = self.next()?;
+        if t.category != TokenType::Identifier || t.value != "target" || t.escaped {
+            return Err(self.error(ParseErrorKind::UnexpectedToken(t.value.into_owned())));
+        }
+        if !self.new_target {
+            return Err(self.error(ParseErrorKind::NewTargetOutsideFunction));
+        }
+        Ok(Expression::NewTarget)
+    }
+    // `super.x` or `super[x]` in a method. without classes there is no constructor
+    // to call `super(...)` in
+    fn parse_super(&mut self) -> Result<Expression, ParseError> {
+        self.next_check(TokenMatcher::from(TokenType::SUPER))?;
+        match self.peek_category()? {
+            Some(TokenType::PERIOD | TokenType::LBRACK) if self.in_method => Ok(Expression::Super),
+            Some(_) => Err(self.error(ParseErrorKind::UnexpectedSuper)),
+            None => Err(self.error(ParseErrorKind::UnexpectedEnd))
+        }
+    }
+    // member accesses, tagged templates and, when allow_call, calls and `?.` after object.
+    // a chain containing `?.` is wrapped in a chain expression
+    fn parse_call_tail(&mut self, object: Expression, allow_call: bool) -> Result<Expression, ParseError> {
+        let mut object = object;
+        let mut in_chain = false;
+        while let Some(t) = self.peek()? {
+            object = match t.category {
+                TokenType::PERIOD => {
+                    self.next()?;
+                    let property = self.parse_property_identifier()?;
+                    member(object, property, false, false)
+                }
+                TokenType::LBRACK => {
+                    self.next()?;
+                    let property = self.with_in(true, |p| p.parse_expression())?;
+                    self.next_check(TokenMatcher::from(TokenType::RBRACK))?;
+                    member(object, property, true, false)
+                }
+                TokenType::LPAREN if allow_call => {
+                    let arguments = self.parse_arguments()?;
+                    call(object, arguments, false)
+                }
+                TokenType::OPTIONAL_CHAIN if allow_call => {
+                    self.next()?;
+                    in_chain = true;
+                    let tp = match self.peek()? {
+                        Some(t) => t.category,
+                        None => return Err(self.error(ParseErrorKind::UnexpectedEnd))
+                    };
+                    match tp {
+                        TokenType::LPAREN => {
+                            let arguments = self.parse_arguments()?;
+                            call(object, arguments, true)
+                        }
+                        TokenType::LBRACK => {
+                            self.next()?;
+                            let property = self.with_in(true, |p| p.parse_expression())?;
+                            self.next_check(TokenMatcher::from(TokenType::RBRACK))?;
+                            member(object, property, true, true)
+                        }
+                        TokenType::TEMPLATE_NO_SUBSTITUTION | TokenType::TEMPLATE_HEAD => {
+                            return Err(self.error(ParseErrorKind::TaggedTemplateInOptionalChain));
+                        }
+                        _ => {
+                            let property = self.parse_property_identifier()?;
+                            member(object, property, false, true)
+                        }
+                    }
+                }
+                TokenType::TEMPLATE_NO_SUBSTITUTION | TokenType::TEMPLATE_HEAD => {
+                    if in_chain {
+                        return Err(self.error(ParseErrorKind::TaggedTemplateInOptionalChain));
+                    }
+                    let quasi = self.parse_template(true)?;
+                    Expression::TaggedTemplate(TaggedTemplateExpr {
+                        tag: Box::new(object),
+                        quasi
+                    })
+                }
+                _ => break
+            };
+        }
+        if in_chain {
+            return Ok(Expression::Chain(ChainExpr { expression: Box::new(object) }));
+        }
+        Ok(object)
+    }
+    // the name after `.` or `?.`, reserved words are fine here
+    fn parse_property_identifier(&mut self) -> Result<Expression, ParseError> {
+        let t = self.next()?;
+        if !t.is_identifier_name() {
+            return Err(self.error(ParseErrorKind::UnexpectedToken(t.value.into_owned())));
+        }
+        Ok(Expression::Identifier(Identifier::new(t.name())))
+    }
+    // `(a, ...b,)`, a trailing comma is allowed
+    fn parse_arguments(&mut self) -> Result<Vec<Expression>, ParseError> {
+        self.next_check(TokenMatcher::from(TokenType::LPAREN))?;
+        let mut arguments = vec![];
+        loop {
+            if self.peek_is(TokenType::RPAREN)? {
+                self.next()?;
+                break;
+            }
+            if self.peek_is(TokenType::ELLIPSIS)? {
+                self.next()?;
+                let argument = self.with_in(true, |p| p.parse_assignment())?;
+                arguments.push(Expression::Spread(SpreadElement { argument: Box::new(argument) }));
+            } else {
+                arguments.push(self.with_in(true, |p| p.parse_assignment())?);
+            }
+            if !self.peek_is(TokenType::RPAREN)? {
+                self.next_check(TokenMatcher::from(TokenType::COMMA))?;
+            }
+        }
+        Ok(arguments)
+    }
+    // `(a, b)`, `()`, `(a, ...b)` or `(a,)`, read once as a parenthesized expression,
+    // the arguments of `async(...)` or arrow parameters. the elements are covers
+    pub(super) fn parse_paren_list(&mut self) -> Result<ParenList, ParseError> {
+        self.next_check(TokenMatcher::from(TokenType::LPAREN))?;
+        let ((elements, trailing_comma), cover) = self.with_cover(|p| p.with_in(true, |p| {
+            let mut elements = vec![];
+            let mut trailing_comma = false;
+            while !p.peek_is(TokenType::RPAREN)? {
+                if p.peek_is(TokenType::ELLIPSIS)? {
+                    p.next()?;
+                    let argument = p.parse_assignment_cover()?;
+                    elements.push(Expression::Spread(SpreadElement { argument: Box::new(argument) }));
+                } else {
+                    elements.push(p.parse_assignment_cover()?);
+                }
+                if !p.peek_is(TokenType::RPAREN)? {
+                    p.next_check(TokenMatcher::from(TokenType::COMMA))?;
+                    trailing_comma = p.peek_is(TokenType::RPAREN)?;
+                }
+            }
+            Ok((elements, trailing_comma))
+        }))?;
+        self.next_check(TokenMatcher::from(TokenType::RPAREN))?;
+        Ok(ParenList {
+            elements,
+            trailing_comma,
+            cover
+        })
+    }
+    // `(a)` or `(a, b)`, the list has to be an expression when no `=>` follows
+    pub(super) fn paren_expression(&self, list: ParenList) -> Result<Expression, ParseError> {
+        if let Some(err) = list.cover.expression {
+            return Err(err);
+        }
+        if list.elements.is_empty() || list.trailing_comma || list.elements.iter().any(|e| matches!(e, Expression::Spread(_))) {
+            return Err(self.error(ParseErrorKind::UnexpectedToken(")".to_string())));
+        }
+        let mut elements = list.elements;
+        let expr = if elements.len() == 1 {
+            elements.remove(0)
+        } else {
+            Expression::Sequence(SequenceExpr { expressions: elements })
+        };
+        Ok(Expression::Paren(Box::new(expr)))
+    }
+    // `async(a, ...b)` when no `=>` follows, a call to a function named async
+    pub(super) fn async_call(&self, callee: Identifier, list: ParenList) -> Result<Expression, ParseError> {
+        if let Some(err) = list.cover.expression {
+            return Err(err);
+        }
+        Ok(call(Expression::Identifier(callee), list.elements, false))
+    }
+    fn parse_primary(&mut self) -> Result<Expression, ParseError> {
+        // an expression can start with a regexp literal, never with a division
+        self.tokens.rescan_as_regexp().map_err(ParseError::from)?;
+        if self.at_function()? {
+            return Ok(Expression::Function(Box::new(self.parse_function(false)?)));
+        }
+        let tp = match self.peek()? {
+            Some(t) => t.category,
+            None => return Err(self.error(ParseErrorKind::UnexpectedEnd))
+        };
+        match tp {
+            TokenType::THIS => {
+                self.next()?;
+                Ok(Expression::This)
+            }
+            TokenType::Identifier => {
+                let t = self.next()?;
+                self.check_identifier(&t)?;
+                Ok(Expression::Identifier(Identifier::new(t.name())))
+            }
+            TokenType::LPAREN => {
+                let list = self.parse_paren_list()?;
+                self.paren_expression(list)
+            }
+            TokenType::TEMPLATE_NO_SUBSTITUTION | TokenType::TEMPLATE_HEAD => {
+                Ok(Expression::Template(self.parse_template(false)?))
+            }
+            TokenType::LBRACK => self.parse_array(),
+            TokenType::LBRACE => self.parse_object(),
+            _ => Ok(Expression::from(self.parse_literal()?))
+        }
+    }
+    // pieces and substitutions alternate until the tail.
+    // an invalid escape is only an error in an untagged template
+    fn parse_template(&mut self, tagged: bool) -> Result<TemplateLiteral, ParseError> {
+        let mut quasis = vec![];
+        let mut expressions = vec![];
+        let mut t = self.next_check(TokenMatcher::from(vec![
+            TokenType::TEMPLATE_NO_SUBSTITUTION,
+            TokenType::TEMPLATE_HEAD
+        ]))?;
+        loop {
+            let tail = matches!(t.category, TokenType::TEMPLATE_NO_SUBSTITUTION | TokenType::TEMPLATE_TAIL);
+            quasis.push(template_element(t, tagged)?);
+            if tail {
+                break;
+            }
+            expressions.push(self.with_in(true, |p| p.parse_expression())?);
+            t = self.next_check(TokenMatcher::from(vec![
+                TokenType::TEMPLATE_MIDDLE,
+                TokenType::TEMPLATE_TAIL
+            ]))?;
+        }
+        Ok(TemplateLiteral {
+            quasis,
+            expressions
+        })
+    }
+    pub fn parse_literal(&mut self) -> Result<Literal, ParseError> {
+        let n = self.next()?;
+        match n.category {
+            TokenType::STRING_LITERAL if self.strict && n.octal_escape => {
+                Err(self.error(ParseErrorKind::OctalEscapeInStrictMode(n.value.into_owned())))
+            }
+            TokenType::STRING_LITERAL => {
+                match n.cooked() {
+                    Some(st) => Ok(Literal::from(st)),
+                    None => Err(self.error(ParseErrorKind::UnexpectedToken(n.value.into_owned())))
+                }
+            }
+            TokenType::NULL_LITERAL => {
+                Ok(Literal::Null)
+            }
+            TokenType::LEGACY_OCTAL_LITERAL | TokenType::NON_OCTAL_DECIMAL_LITERAL if self.strict => {
+                Err(self.error(ParseErrorKind::OctalLiteralInStrictMode(n.value.into_owned())))
+            }
+            TokenType::NUMERIC_LITERAL | TokenType::LEGACY_OCTAL_LITERAL | TokenType::NON_OCTAL_DECIMAL_LITERAL => {
+                match n.number {
+                    Some(num) => Ok(Literal::from(num)),
+                    None => Err(self.error(ParseErrorKind::UnexpectedToken(n.value.into_owned())))
+                }
+            }
+            TokenType::BIGINT_LITERAL => {
+                match n.bigint {
+                    Some(big) => Ok(Literal::from(big)),
+                    None => Err(self.error(ParseErrorKind::UnexpectedToken(n.value.into_owned())))
+                }
+            }
+            TokenType::REGEXP_LITERAL => {
+                Ok(Literal::RegExp(RegExpLiteral {
+                    pattern: n.value.into_owned(),
+                    flags: n.regex_flags.unwrap_or_default().to_string()
+                }))
+            }
+            TokenType::TRUE_LITERAL | TokenType::FALSE_LITERAL => {
+                Ok(Literal::from(n.value == "true"))
+            }
+            _ => {
+                Err(self.error(ParseErrorKind::UnexpectedToken(n.value.into_owned())))
+            }
+        }
+    }
+    // `a`, `if`, `"a"`, `1`, `1n` or `[a]`, reserved words are names here
+    pub(super) fn parse_property_key(&mut self) -> Result<PropertyKey, ParseError> {
+        let (tp, is_name) = match self.peek()? {
+            Some(t) => (t.category, t.is_identifier_name()),
+            None => return Err(self.error(ParseErrorKind::UnexpectedEnd))
+        };
+        if is_name {
+            let t = self.next()?;
+            return Ok(PropertyKey::Identifier(Identifier::new(t.name())));
+        }
+        match tp {
+            TokenType::LBRACK => {
+                self.next()?;
+                let key = self.with_in(true, |p| p.parse_assignment())?;
+                self.next_check(TokenMatcher::from(TokenType::RBRACK))?;
+                Ok(PropertyKey::Computed(Box::new(key)))
+            }
+            // a string key is interned like a name, unless it has a lone surrogate.
+            // without escapes the source text is the value
+            TokenType::STRING_LITERAL if self.peek()?.is_some_and(|t| t.string.is_none()) => {
+                let t = self.next()?;
+                Ok(PropertyKey::String(t.name()))
+            }
+            TokenType::STRING_LITERAL => {
+                match self.parse_literal()? {
+                    Literal::String(s) => match String::from_utf16(s.value.as_units()) {
+                        Ok(name) => Ok(PropertyKey::String(Atom::from(name.as_str()))),
+                        Err(_) => Ok(PropertyKey::Literal(Literal::String(s)))
+                    },
+                    literal => Ok(PropertyKey::Literal(literal))
+                }
+            }
+            TokenType::NUMERIC_LITERAL | TokenType::BIGINT_LITERAL |
+            TokenType::LEGACY_OCTAL_LITERAL | TokenType::NON_OCTAL_DECIMAL_LITERAL => {
+                Ok(PropertyKey::Literal(self.parse_literal()?))
+            }
+            _ => {
+                let t = self.next()?;
+                Err(self.error(ParseErrorKind::UnexpectedToken(t.value.into_owned())))
+            }
+        }
+    }
+    // what `++`, `--` and assignment operators can change
+    pub(super) fn check_simple_target(&self, e: &Expression) -> Result<(), ParseError> {
+        if !e.is_simple_target() {
+            return Err(self.error(ParseErrorKind::InvalidAssignmentTarget));
+        }
+        if let Expression::Identifier(id) = e.unparenthesized() {
+            if self.strict && (id.value == Atom::EVAL || id.value == Atom::ARGUMENTS) {
+                return Err(self.error(ParseErrorKind::StrictModeAssignment(id.value.to_string())));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn member(object: Expression, property: Expression, computed: bool, optional: bool) -> Expression {
+    Expression::Member(MemberExpr {
+        object: Box::new(object),
+        property: Box::new(property),
+        computed,
+        optional
+    })
+}
+
+fn call(callee: Expression, arguments: Vec<Expression>, optional: bool) -> Expression {
+    Expression::Call(CallExpr {
+        callee: Box::new(callee),
+        arguments,
+        optional
+    })
+}
+
+fn template_element(t: Token, tagged: bool) -> Result<TemplateElement, ParseError> {
+    let cooked = t.cooked();
+    if let Some(e) = t.escape_error {
+        if !tagged {
+            return Err(ParseError::from(e));
+        }
+    }
+    Ok(TemplateElement {
+        cooked,
+        raw: t.raw.map(|r| r.into_owned()).unwrap_or_default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lexer::lexer::Lexer;
+    use crate::ast::expression::*;
+    use crate::ast::statement::{Program, Statement, ExpressionStatement};
+    use crate::ast::variable::Pattern;
+    use crate::ast::function::FunctionBody;
+    use crate::parser::Parser;
+    use crate::parser::error::ParseErrorKind;
+
+    fn program(source: &str) -> Result<Program, ParseErrorKind> {
+        Parser::new(Lexer::new(source)).parse().map_err(|e| e.kind)
+    }
+
+    // the expression of a program that is one expression statement
+    fn parse(source: &str) -> Result<Expression, ParseErrorKind> {
+        let mut program = program(source)?;
+        match program.body.pop() {
+            Some(Statement::Expression(ExpressionStatement { expression: e })) if program.body.is_empty() => Ok(e),
+            s => panic!("{:?} is {:?}", source, s)
+        }
+    }
+
+    // the tree of an expression with every operator node in parentheses
+    fn tree(source: &str) -> String {
+        match parse(source) {
+            Ok(e) => show(&e),
+            Err(e) => panic!("{:?}: {}", source, e.message())
+        }
+    }
+
+    fn show(e: &Expression) -> String {
+        match e {
+            Expression::Identifier(id) => id.value.to_string(),
+            Expression::Literal(Literal::Number(n)) => n.value.to_string(),
+            Expression::Paren(e) => format!("[{}]", show(e)),
+            Expression::Bin(b) => format!("({:?} {} {})", b.operator, show(&b.left), show(&b.right)),
+            Expression::Logical(l) => format!("({:?} {} {})", l.operator, show(&l.left), show(&l.right)),
+            Expression::Unary(u) => format!("({:?} {})", u.operator, show(&u.argument)),
+            Expression::Update(u) => format!("({:?}{} {})", u.operator, if u.prefix { "" } else { "Post" }, show(&u.argument)),
+            Expression::Await(a) => format!("(Await {})", show(&a.argument)),
+            Expression::Conditional(c) => format!("(? {} {} {})", show(&c.test), show(&c.consequent), show(&c.alternate)),
+            Expression::Assign(a) => format!("({:?}= {} {})", a.operator, show_target(&a.left), show(&a.right)),
+            Expression::Member(m) => format!("({} {} {})", if m.optional { "?." } else { "." }, show(&m.object), show(&m.property)),
+            Expression::Call(c) => {
+                let arguments: Vec<String> = c.arguments.iter().map(show).collect();
+                format!("({} {} {})", if c.optional { "?call" } else { "call" }, show(&c.callee), arguments.join(" "))
+            }
+            Expression::New(n) => {
+                let arguments: Vec<String> = n.arguments.iter().map(show).collect();
+                format!("(new {} {})", show(&n.callee), arguments.join(" "))
+            }
+            Expression::Chain(c) => format!("(chain {})", show(&c.expression)),
+            Expression::Sequence(s) => {
+                let expressions: Vec<String> = s.expressions.iter().map(show).collect();
+                format!("(, {})", expressions.join(" "))
+            }
+            Expression::Spread(s) => format!("(... {})", show(&s.argument)),
+            Expression::Array(a) => {
+                let elements: Vec<String> = a.elements.iter().map(|e| e.as_ref().map_or("_".to_string(), show)).collect();
+                format!("(array {})", elements.join(" "))
+            }
+            Expression::Object(o) => {
+                let members: Vec<String> = o.properties.iter().map(|m| match m {
+                    ObjectMember::Property(p) => format!("{}:{}", show_key(&p.key), show(&p.value)),
+                    ObjectMember::Spread(s) => format!("(... {})", show(&s.argument))
+                }).collect();
+                format!("(object {})", members.join(" "))
+            }
+            Expression::Function(f) => {
+                let mut params: Vec<String> = f.params.iter().map(show_target).collect();
+                params.extend(f.rest.iter().map(|r| format!("(... {})", show_target(r))));
+                let body = match &f.body {
+                    FunctionBody::Expression(e) => show(e),
+                    FunctionBody::Block(statements) => format!("{{{}}}", statements.len())
+                };
+                let kind = match (f.arrow, f.is_async) {
+                    (true, false) => "=>",
+                    (true, true) => "async =>",
+                    (false, _) => "function"
+                };
+                format!("({} ({}) {})", kind, params.join(" "), body)
+            }
+            e => format!("{:?}", e)
+        }
+    }
+
+    fn show_target(p: &Pattern) -> String {
+        match p {
+            Pattern::Identifier(id) => id.value.to_string(),
+            Pattern::Member(e) => show(e),
+            Pattern::Assign(a) => format!("(= {} {})", show_target(&a.left), show(&a.right)),
+            Pattern::Array(a) => {
+                let mut elements: Vec<String> = a.elements.iter().map(|e| e.as_ref().map_or("_".to_string(), show_target)).collect();
+                elements.extend(a.rest.iter().map(|r| format!("(... {})", show_target(r))));
+                format!("[{}]", elements.join(" "))
+            }
+            Pattern::Object(o) => {
+                let mut properties: Vec<String> = o.properties.iter().map(|p| format!("{}:{}", show_key(&p.key), show_target(&p.value))).collect();
+                properties.extend(o.rest.iter().map(|r| format!("(... {})", show_target(r))));
+                format!("{{{}}}", properties.join(" "))
+            }
+        }
+    }
+
+    fn show_key(key: &PropertyKey) -> String {
+        match key {
+            PropertyKey::Identifier(id) => id.value.to_string(),
+            PropertyKey::String(name) => format!("{:?}", name.to_string()),
+            PropertyKey::Literal(Literal::Number(n)) => n.value.to_string(),
+            PropertyKey::Literal(l) => format!("{:?}", l),
+            PropertyKey::Computed(e) => format!("[{}]", show(e))
+        }
+    }
+
+    #[test]
+    fn binary_precedence() {
+        assert_eq!(tree("a + b * c"), "(Add a (Mul b c))");
+        assert_eq!(tree("a * b + c"), "(Add (Mul a b) c)");
+        assert_eq!(tree("a - b - c"), "(Sub (Sub a b) c)");
+        assert_eq!(tree("a << b + c"), "(Shl a (Add b c))");
+        assert_eq!(tree("a < b == c"), "(Eq (Lt a b) c)");
+        assert_eq!(tree("a & b | c ^ d"), "(BitOr (BitAnd a b) (BitXor c d))");
+        assert_eq!(tree("a || b && c"), "(Or a (And b c))");
+        assert_eq!(tree("a in b instanceof c"), "(InstanceOf (In a b) c)");
+    }
+
+    #[test]
+    fn exponent_is_right_associative() {
+        assert_eq!(tree("a ** b ** c"), "(Exp a (Exp b c))");
+        assert_eq!(tree("a * b ** c"), "(Mul a (Exp b c))");
+        assert_eq!(tree("(-a) ** b"), "(Exp [(Sub a)] b)");
+        assert_eq!(tree("a ** -b"), "(Exp a (Sub b))");
+    }
+
+    #[test]
+    fn exponent_unary_restriction() {
+        for source in ["-a ** b", "!a ** b", "typeof a ** b", "delete a.b ** c", "void a ** b", "~a ** b"] {
+            assert!(matches!(parse(source), Err(ParseErrorKind::UnparenthesizedUnaryExponent)), "{}", source);
+        }
+        assert!(matches!(program("async function f() { await a ** b }"), Err(ParseErrorKind::UnparenthesizedUnaryExponent)));
+        assert_eq!(tree("++a ** b"), "(Exp (Inc a) b)");
+        assert_eq!(tree("a++ ** b"), "(Exp (IncPost a) b)");
+    }
+
+    #[test]
+    fn nullish_mixing_restriction() {
+        assert_eq!(tree("a ?? b ?? c"), "(Nullish (Nullish a b) c)");
+        assert_eq!(tree("(a || b) ?? c"), "(Nullish [(Or a b)] c)");
+        assert_eq!(tree("a ?? (b && c)"), "(Nullish a [(And b c)])");
+        for source in ["a ?? b || c", "a || b ?? c", "a && b ?? c", "a ?? b && c"] {
+            assert!(matches!(parse(source), Err(ParseErrorKind::MixedNullishCoalescing)), "{}", source);
+        }
+    }
+
+    #[test]
+    fn conditional_and_assignment() {
+        assert_eq!(tree("a ? b : c ? d : e"), "(? a b (? c d e))");
+        assert_eq!(tree("a = b = c"), "(Assign= a (Assign= b c))");
+        assert_eq!(tree("a += b ? c : d"), "(Add= a (? b c d))");
+        assert_eq!(tree("a.b ??= c"), "(Nullish= (. a b) c)");
+        assert_eq!(tree("(a) = b"), "(Assign= a b)");
+        for source in ["a + b = c", "a() = b", "1 = a", "++a++", "a?.b = c", "(a, b) = c"] {
+            assert!(matches!(parse(source), Err(ParseErrorKind::InvalidAssignmentTarget)), "{}", source);
+        }
+    }
+
+    #[test]
+    fn unary_and_update() {
+        assert_eq!(tree("!-a"), "(Bang (Sub a))");
+        assert_eq!(tree("typeof a.b"), "(Typeof (. a b))");
+        assert_eq!(tree("--a"), "(Dec a)");
+        assert!(matches!(program("\"use strict\"; delete a"), Err(ParseErrorKind::DeleteIdentifierInStrictMode(_))));
+        assert!(matches!(program("\"use strict\"; eval++"), Err(ParseErrorKind::StrictModeAssignment(_))));
+    }
+
+    #[test]
+    fn new_call_and_member() {
+        assert_eq!(tree("new a.b(c)"), "(new (. a b) c)");
+        assert_eq!(tree("new a"), "(new a )");
+        assert_eq!(tree("new new a()()"), "(new (new a ) )");
+        assert_eq!(tree("new a().b"), "(. (new a ) b)");
+        assert_eq!(tree("a.b(c)[d]"), "(. (call (. a b) c) d)");
+        assert_eq!(tree("a.if.class"), "(. (. a if) class)");
+    }
+
+    #[test]
+    fn optional_chain() {
+        assert_eq!(tree("a?.b.c"), "(chain (. (?. a b) c))");
+        assert_eq!(tree("a?.(b)"), "(chain (?call a b))");
+        assert_eq!(tree("a?.[b]"), "(chain (?. a b))");
+        assert_eq!(tree("(a?.b).c"), "(. [(chain (?. a b))] c)");
+        assert!(matches!(parse("a?.b`c`"), Err(ParseErrorKind::TaggedTemplateInOptionalChain)));
+        assert!(matches!(parse("a?.`c`"), Err(ParseErrorKind::TaggedTemplateInOptionalChain)));
+    }
+
+    #[test]
+    fn array_and_object_literals() {
+        assert_eq!(tree("[a, , ...b,]"), "(array a _ (... b))");
+        assert_eq!(tree("[,]"), "(array _)");
+        assert_eq!(tree("({ a, b: 1, [c]: 2, 3: d, \"e\": f, ...g })"), "[(object a:a b:1 [c]:2 3:d \"e\":f (... g))]");
+        for source in ["({ get: 1, set: 2, async: 3 })", "({ get a() {}, set a(v) {} })", "({ async a() {}, *b() {}, async *c() {} })", "({ get() {}, async() {} })"] {
+            assert!(parse(source).is_ok(), "{}", source);
+        }
+    }
+
+    #[test]
+    fn duplicate_proto() {
+        for source in ["({ __proto__: a, __proto__: b })", "({ __proto__: a, \"__proto__\": b })", "({ '__proto__': a, __proto__: b })"] {
+            assert!(matches!(parse(source), Err(ParseErrorKind::DuplicateProto)), "{}", source);
+        }
+        // only plain properties set the prototype, and a pattern sets nothing
+        for source in ["({ __proto__: a, __proto__ })", "({ __proto__: a, __proto__() {} })", "({ __proto__: a, [\"__proto__\"]: b })", "({ __proto__: a, __proto__: b } = c)"] {
+            assert!(parse(source).is_ok(), "{}", source);
+        }
+    }
+
+    #[test]
+    fn destructuring_assignment() {
+        assert_eq!(tree("[a, b] = [b, a]"), "(Assign= [a b] (array b a))");
+        assert_eq!(tree("[a, , b = 1, ...c.d] = e"), "(Assign= [a _ (= b 1) (... (. c d))] e)");
+        assert_eq!(tree("({ a = 1, b: { c }, ...d } = e)"), "[(Assign= {a:(= a 1) b:{c:c} (... d)} e)]");
+        assert_eq!(tree("[[a], { b: c.d }] = e"), "(Assign= [[a] {b:(. c d)}] e)");
+        assert_eq!(tree("[(a)] = b"), "(Assign= [a] b)");
+        assert!(program("for ([a, b] of c);").is_ok());
+        assert!(program("for ({ a = 1 } in b);").is_ok());
+    }
+
+    #[test]
+    fn destructuring_assignment_rejected() {
+        assert!(matches!(parse("({ a = 1 })"), Err(ParseErrorKind::ShorthandInitializer)));
+        assert!(matches!(parse("[{ a = 1 }]"), Err(ParseErrorKind::ShorthandInitializer)));
+        assert!(matches!(parse("[{ a = 1 }.b] = c"), Err(ParseErrorKind::ShorthandInitializer)));
+        for source in ["[...a, b] = c", "[...a,] = c", "({ ...a, b } = c)", "[a + 1] = b", "({ a: 1 } = b)", "[...a = 1] = b", "({ a() {} } = b)"] {
+            assert!(matches!(parse(source), Err(ParseErrorKind::InvalidAssignmentTarget)), "{}", source);
+        }
+    }
+
+    #[test]
+    fn arrow_functions() {
+        assert_eq!(tree("a => a"), "(=> (a) a)");
+        assert_eq!(tree("() => {}"), "(=> () {0})");
+        assert_eq!(tree("(a, b,) => a + b"), "(=> (a b) (Add a b))");
+        assert_eq!(tree("(a = 1, [b], { c }, ...d) => 0"), "(=> ((= a 1) [b] {c:c} (... d)) 0)");
+        assert_eq!(tree("async a => a"), "(async => (a) a)");
+        assert_eq!(tree("async (a) => { await a }"), "(async => (a) {1})");
+        assert_eq!(tree("a => b => c"), "(=> (a) (=> (b) c))");
+        assert_eq!(tree("a ? b => c : d => e"), "(? a (=> (b) c) (=> (d) e))");
+        assert_eq!(tree("a = () => b, c"), "(, (Assign= a (=> () b)) c)");
+        // without the arrow, the same tokens are a call or a parenthesized expression
+        assert_eq!(tree("async(a, ...b)"), "(call async a (... b))");
+        assert_eq!(tree("(a, b)"), "[(, a b)]");
+    }
+
+    #[test]
+    fn arrow_functions_rejected() {
+        for source in ["(a, ...b,) => 0", "(...a, b) => 0", "((a)) => 0", "(a + b) => 0", "(a.b) => 0", "([a.b]) => 0"] {
+            assert!(matches!(parse(source), Err(ParseErrorKind::InvalidArrowParameter)), "{}", source);
+        }
+        for source in ["(a)\n=> 0", "async a\n=> 0", "({ a: 1 }) => 0", "() => {}(1)", "()", "(a, ...b)", "(...a)"] {
+            assert!(parse(source).is_err(), "{:?}", source);
+        }
+        // a nested arrow that is thrown away is still read only once
+        let nested = format!("{}a{}", "(".repeat(25), ")".repeat(25));
+        assert_eq!(tree(&nested), format!("{}a{}", "[".repeat(25), "]".repeat(25)));
+    }
+
+    #[test]
+    fn function_expressions() {
+        assert_eq!(tree("(function (a, b = 1, ...c) {})"), "[(function (a (= b 1) (... c)) {0})]");
+        assert!(program("function f([a, { b }] = [], ...[c]) { \"use strict\" }").is_err());
+        assert!(program("function* g() { yield 1 } async function h() { await 1 } async function* i() {}").is_ok());
+    }
+
+    #[test]
+    fn super_property_in_methods() {
+        for source in ["({ a() { return super.b } })", "({ get a() { return super[b] } })", "({ async *a() { super.b = 1 } })", "({ a() { return () => super.b } })"] {
+            assert!(program(source).is_ok(), "{}", source);
+        }
+        for source in ["super.a", "super[a]", "super;", "function f() { super.a }", "({ a: function () { super.b } })", "({ a() { function f() { super.b } } })", "() => super.a", "new super.a"] {
+            assert!(matches!(program(source), Err(ParseErrorKind::UnexpectedSuper)), "{}", source);
+        }
+        // there are no class constructors to call the parent one from
+        for source in ["super()", "({ a() { super() } })", "function f() { super() }"] {
+            assert!(matches!(program(source), Err(ParseErrorKind::UnexpectedSuper)), "{}", source);
+        }
+    }
+
+    #[test]
+    fn new_target() {
+        for source in ["function f() { return new.target }", "function f() { new.target.a; new.target() }", "function f() { return () => new.target }", "({ a() { new.target } })", "function f() { new new.target() }"] {
+            assert!(program(source).is_ok(), "{}", source);
+        }
+        for source in ["new.target", "() => new.target", "({ a: new.target })"] {
+            assert!(matches!(program(source), Err(ParseErrorKind::NewTargetOutsideFunction)), "{}", source);
+        }
+        for source in ["function f() { new.targets }", "function f() { new.t\\u0061rget }", "function f() { new.if }"] {
+            assert!(matches!(program(source), Err(ParseErrorKind::UnexpectedToken(_))), "{}", source);
+        }
+    }
+
+    #[test]
+    fn optional_chain_in_new() {
+        for source in ["new a?.b", "new a?.b()", "x = new a?.()", "new a.b?.c()", "new new a?.b()"] {
+            assert!(matches!(parse(source), Err(ParseErrorKind::OptionalChainInNew)), "{}", source);
+        }
+        // the chain may follow the arguments
+        assert_eq!(tree("new a()?.b"), "(chain (?. (new a ) b))");
+        assert_eq!(tree("new a().b?.()"), "(chain (?call (. (new a ) b) ))");
+    }
+
+    #[test]
+    fn legacy_octal_in_strict_mode() {
+        assert!(program("010; 089; ({ 010: a })").is_ok());
+        let sources = [
+            "'use strict'; 010",
+            "'use strict'; 089",
+            "'use strict'; ({ 08.5: a })",
+            "function f() { 'use strict'; return 017 }",
+            "'use strict'; function f() { 09 }"
+        ];
+        for source in sources {
+            assert!(matches!(program(source), Err(ParseErrorKind::OctalLiteralInStrictMode(_))), "{}", source);
+        }
+    }
+}
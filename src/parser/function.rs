@@ -0,0 +1,361 @@
+use crate::atom::Atom;
+use crate::lexer::token::{TokenType, TokenMatcher};
+use crate::ast::expression::{Expression, YieldExpr, PropertyKind};
+use crate::ast::function::{Function, FunctionBody};
+use crate::ast::statement::Statement;
+use crate::ast::variable::{Identifier, Pattern};
+use super::{Parser, Label};
+use super::expr::ParenList;
+use super::error::{ParseError, ParseErrorKind};
+use super::scope::{Scope, ScopeType};
+
+// the parser state a function body starts over with, put back after the function
+struct Context {
+    strict: bool,
+    allow_in: bool,
+    in_function: bool,
+    in_async: bool,
+    in_generator: bool,
+    in_method: bool,
+    new_target: bool,
+    labels: Vec<Label>,
+    loop_depth: u32,
+    breakable_depth: u32
+}
+
+// `(a, [b], c = 1, ...d)`
+struct Params {
+    list: Vec<Pattern>,
+    rest: Option<Box<Pattern>>
+}
+
+impl Params {
+    // plain names only, no defaults, destructuring or rest
+    fn is_simple(&self) -> bool {
+        self.rest.is_none() && self.list.iter().all(|p| matches!(p, Pattern::Identifier(_)))
+    }
+    fn bound_names(&self) -> Vec<Atom> {
+        let mut names = vec![];
+        for p in &self.list {
+            p.bound_names(&mut names);
+        }
+        if let Some(rest) = &self.rest {
+            rest.bound_names(&mut names);
+        }
+        names
+    }
+}
+
+impl<'a> Parser<'a> {
+    // labels, loops and `in` don't reach into a function, and it has its own scope
+    fn enter_function(&mut self, is_async: bool, generator: bool) -> Context {
+        let saved = Context {
+            strict: self.strict,
+            allow_in: self.allow_in,
+            in_function: self.in_function,
+            in_async: self.in_async,
+            in_generator: self.in_generator,
+            in_method: self.in_method,
+            new_target: self.new_target,
+            labels: std::mem::take(&mut self.labels),
+            loop_depth: self.loop_depth,
+            breakable_depth: self.breakable_depth
+        };
+        self.allow_in = true;
+        self.in_function = true;
+        self.in_async = is_async;
+        self.in_generator = generator;
+        self.in_method = false;
+        self.new_target = true;
+        self.loop_depth = 0;
+        self.breakable_depth = 0;
+        self.scope_stack.push(Scope::new(ScopeType::ScopeFunction));
+        saved
+    }
+    fn leave_function(&mut self, saved: Context) {
+        self.scope_stack.pop();
+        self.strict = saved.strict;
+        self.allow_in = saved.allow_in;
+        self.in_function = saved.in_function;
+        self.in_async = saved.in_async;
+        self.in_generator = saved.in_generator;
+        self.in_method = saved.in_method;
+        self.new_target = saved.new_target;
+        self.labels = saved.labels;
+        self.loop_depth = saved.loop_depth;
+        self.breakable_depth = saved.breakable_depth;
+    }
+    // `function`, or `async function` without a line break in between
+    pub(super) fn at_function(&mut self) -> Result<bool, ParseError> {
+        let (tp, is_async) = match self.peek()? {
+            Some(t) => (t.category, t.is_contextual(TokenType::ASYNC)),
+            None => return Ok(false)
+        };
+        if tp == TokenType::FUNCTION {
+            return Ok(true);
+        }
+        Ok(is_async && self.peek_nth(1)?.is_some_and(|t| t.category == TokenType::FUNCTION && !t.newline_before))
+    }
+    // `function f(a) {}` or `async function* f() {}`, a declaration needs the name
+    // and binds it in the enclosing scope
+    pub(super) fn parse_function(&mut self, declaration: bool) -> Result<Function, ParseError> {
+        let is_async = !self.peek_is(TokenType::FUNCTION)?;
+        if is_async {
+            self.next()?;
+        }
+        self.next_check(TokenMatcher::from(TokenType::FUNCTION))?;
+        let generator = self.peek_is(TokenType::MUL)?;
+        if generator {
+            self.next()?;
+        }
+        let id = if declaration || self.peek_is(TokenType::Identifier)? {
+            let t = self.next_check(TokenMatcher::from(TokenType::Identifier))?;
+            self.check_identifier(&t)?;
+            if declaration {
+                // var-like at the top of a script or function, lexical in a block
+                let var = self.scope_stack.last().is_some_and(|s| s.is_var_scope());
+                self.declare_names(&[t.name()], var)?;
+            }
+            Some(Identifier::new(t.name()))
+        } else {
+            None
+        };
+        let saved = self.enter_function(is_async, generator);
+        let function = self.parse_function_rest(id, is_async, generator);
+        self.leave_function(saved);
+        function
+    }
+    fn parse_function_rest(&mut self, id: Option<Identifier>, is_async: bool, generator: bool) -> Result<Function, ParseError> {
+        let params = self.parse_params()?;
+        self.declare_params(&params, false)?;
+        let body = self.parse_function_body(id.as_ref(), &params, false)?;
+        Ok(Function {
+            id,
+            params: params.list,
+            rest: params.rest,
+            body: FunctionBody::Block(body),
+            is_async,
+            generator,
+            arrow: false,
+            strict: self.strict
+        })
+    }
+    // `(a, [b], c = 1, ...d)`, a trailing comma is allowed unless after the rest
+    fn parse_params(&mut self) -> Result<Params, ParseError> {
+        self.next_check(TokenMatcher::from(TokenType::LPAREN))?;
+        let mut list = vec![];
+        let mut rest = None;
+        while !self.peek_is(TokenType::RPAREN)? {
+            if self.peek_is(TokenType::ELLIPSIS)? {
+                self.next()?;
+                rest = Some(Box::new(self.parse_binding_target()?));
+                break;
+            }
+            list.push(self.parse_binding_element()?);
+            if !self.peek_is(TokenType::RPAREN)? {
+                self.next_check(TokenMatcher::from(TokenType::COMMA))?;
+            }
+        }
+        self.next_check(TokenMatcher::from(TokenType::RPAREN))?;
+        Ok(Params {
+            list,
+            rest
+        })
+    }
+    // parameters are var-like in the function scope, `function f(a) { let a }` is an error.
+    // a name may only repeat in a sloppy mode function with simple parameters,
+    // arrows and methods are unique
+    fn declare_params(&mut self, params: &Params, unique: bool) -> Result<(), ParseError> {
+        let names = params.bound_names();
+        self.declare_names(&names, true)?;
+        if self.strict || unique || !params.is_simple() {
+            check_duplicates(&names).map_err(|k| self.error(k))?;
+        }
+        Ok(())
+    }
+    // `{ body }`. a "use strict" in the body makes its own parameters and name strict too,
+    // so they are checked again
+    fn parse_function_body(&mut self, id: Option<&Identifier>, params: &Params, unique: bool) -> Result<Vec<Statement>, ParseError> {
+        let was_strict = self.strict;
+        self.next_check(TokenMatcher::from(TokenType::LBRACE))?;
+        let (body, use_strict) = self.parse_body()?;
+        self.next_check(TokenMatcher::from(TokenType::RBRACE))?;
+        if use_strict && !params.is_simple() {
+            return Err(self.error(ParseErrorKind::UseStrictWithNonSimpleParameters));
+        }
+        if self.strict && !was_strict {
+            let names = params.bound_names();
+            for name in id.map(|id| id.value).iter().chain(names.iter()) {
+                self.check_binding_name(*name)?;
+            }
+            if !unique {
+                check_duplicates(&names).map_err(|k| self.error(k))?;
+            }
+        }
+        Ok(body)
+    }
+    // the parameters and body after a method name, `(a) {}`.
+    // a getter has no parameters and a setter exactly one
+    pub(super) fn parse_method(&mut self, is_async: bool, generator: bool, kind: PropertyKind) -> Result<Function, ParseError> {
+        let saved = self.enter_function(is_async, generator);
+        self.in_method = true;
+        let function = self.parse_method_rest(is_async, generator, kind);
+        self.leave_function(saved);
+        function
+    }
+    fn parse_method_rest(&mut self, is_async: bool, generator: bool, kind: PropertyKind) -> Result<Function, ParseError> {
+        let params = self.parse_params()?;
+        let count = match kind {
+            PropertyKind::Init => true,
+            PropertyKind::Get => params.list.is_empty() && params.rest.is_none(),
+            PropertyKind::Set => params.list.len() == 1 && params.rest.is_none()
+        };
+        if !count {
+            return Err(self.error(ParseErrorKind::InvalidAccessorParameters));
+        }
+        self.declare_params(&params, true)?;
+        let body = self.parse_function_body(None, &params, true)?;
+        Ok(Function {
+            id: None,
+            params: params.list,
+            rest: params.rest,
+            body: FunctionBody::Block(body),
+            is_async,
+            generator,
+            arrow: false,
+            strict: self.strict
+        })
+    }
+    // `a => ...`, `(a, b) => ...`, `async a => ...` or `async (a) => ...` at the start
+    // of an assignment expression. a parenthesized list is read once, when no `=>`
+    // follows it the expression or `async(...)` call it is continues in self.primary
+    pub(super) fn parse_arrow_start(&mut self) -> Result<Option<Expression>, ParseError> {
+        let is_async = match self.peek()? {
+            Some(t) => t.is_contextual(TokenType::ASYNC),
+            None => return Ok(None)
+        };
+        let is_async = is_async && self.peek_nth(1)?.is_some_and(|t| {
+            !t.newline_before && matches!(t.category, TokenType::Identifier | TokenType::LPAREN)
+        });
+        let n = if is_async { 1 } else { 0 };
+        let first = match self.peek_nth(n)? {
+            Some(t) => t.category,
+            None => return Ok(None)
+        };
+        let params = match first {
+            TokenType::Identifier if self.peek_nth(n + 1)?.is_some_and(|t| t.category == TokenType::ARROW) => {
+                if is_async {
+                    self.next()?;
+                }
+                let t = self.next()?;
+                self.check_identifier(&t)?;
+                Params {
+                    list: vec![Pattern::Identifier(Identifier::new(t.name()))],
+                    rest: None
+                }
+            }
+            TokenType::LPAREN => {
+                let callee = if is_async {
+                    Some(Identifier::new(self.next()?.name()))
+                } else {
+                    None
+                };
+                let list = self.parse_paren_list()?;
+                // `=>` has to be on the line of the parameters
+                if !self.peek_is(TokenType::ARROW)? || self.newline_before_next()? {
+                    self.primary = Some(match callee {
+                        Some(callee) => self.async_call(callee, list)?,
+                        None => self.paren_expression(list)?
+                    });
+                    return Ok(None);
+                }
+                self.to_params(list)?
+            }
+            _ => return Ok(None)
+        };
+        if self.newline_before_next()? {
+            let t = self.next()?;
+            return Err(self.error(ParseErrorKind::UnexpectedToken(t.value.into_owned())));
+        }
+        self.next_check(TokenMatcher::from(TokenType::ARROW))?;
+        let allow_in = self.allow_in;
+        let saved = self.enter_function(is_async, false);
+        // an arrow has no `super` or `new.target` of its own, it sees the ones around it
+        self.in_method = saved.in_method;
+        self.new_target = saved.new_target;
+        let function = self.parse_arrow_body(params, is_async, allow_in);
+        self.leave_function(saved);
+        Ok(Some(Expression::Function(Box::new(function?))))
+    }
+    // the list before `=>` read again as parameters, a spread is the rest
+    // when it comes last without a trailing comma
+    fn to_params(&self, list: ParenList) -> Result<Params, ParseError> {
+        if let Some(err) = list.cover.pattern {
+            return Err(err);
+        }
+        let count = list.elements.len();
+        let mut params = Params {
+            list: vec![],
+            rest: None
+        };
+        for (i, e) in list.elements.into_iter().enumerate() {
+            match e {
+                Expression::Spread(s) if i + 1 == count && !list.trailing_comma && !matches!(*s.argument, Expression::Assign(_)) => {
+                    params.rest = Some(Box::new(self.to_binding(*s.argument)?));
+                }
+                e => params.list.push(self.to_binding(e)?)
+            }
+        }
+        Ok(params)
+    }
+    // a block body, or a concise body that is a single assignment expression
+    fn parse_arrow_body(&mut self, params: Params, is_async: bool, allow_in: bool) -> Result<Function, ParseError> {
+        self.declare_params(&params, true)?;
+        let body = if self.peek_is(TokenType::LBRACE)? {
+            FunctionBody::Block(self.parse_function_body(None, &params, true)?)
+        } else {
+            FunctionBody::Expression(Box::new(self.with_in(allow_in, |p| p.parse_assignment())?))
+        };
+        Ok(Function {
+            id: None,
+            params: params.list,
+            rest: params.rest,
+            body,
+            is_async,
+            generator: false,
+            arrow: true,
+            strict: self.strict
+        })
+    }
+    // `yield`, `yield x` or `yield* x` in a generator body. without an expression
+    // after it on the same line, yield has no argument
+    pub(super) fn parse_yield(&mut self) -> Result<Expression, ParseError> {
+        self.next()?;
+        let delegate = !self.newline_before_next()? && self.peek_is(TokenType::MUL)?;
+        if delegate {
+            self.next()?;
+        }
+        let ends = !delegate && (self.newline_before_next()? || matches!(self.peek_category()?, Some(
+            TokenType::RPAREN | TokenType::RBRACK | TokenType::RBRACE | TokenType::COMMA |
+            TokenType::SEMICOLON | TokenType::COLON | TokenType::TEMPLATE_MIDDLE | TokenType::TEMPLATE_TAIL
+        )));
+        let argument = if ends {
+            None
+        } else {
+            Some(Box::new(self.parse_assignment()?))
+        };
+        Ok(Expression::Yield(YieldExpr {
+            argument,
+            delegate
+        }))
+    }
+}
+
+fn check_duplicates(names: &[Atom]) -> Result<(), ParseErrorKind> {
+    for (i, name) in names.iter().enumerate() {
+        if names[..i].contains(name) {
+            return Err(ParseErrorKind::DuplicateParameter(name.to_string()));
+        }
+    }
+    Ok(())
+}
@@ -1,91 +1,238 @@
 mod scope;
 mod error;
-use crate::{lexer::{lexer::Lexer, token::{Token, TokenType, TokenMatcher}}, ast::expression::{Expression, Literal}};
-use crate::ast::{variable::{VariableDeclaration, Variable}};
+mod tokens;
+mod expr;
+mod statement;
+mod pattern;
+mod function;
+mod object;
+use crate::lexer::{lexer::Lexer, token::{Token, TokenType, TokenMatcher, is_strict_mode_reserved}};
+use crate::atom::Atom;
+use crate::input::Span;
+use crate::ast::statement::Program;
+use crate::ast::expression::Expression;
+use scope::{Scope, ScopeType};
+use tokens::TokenBuffer;
 pub struct Parser<'a> {
-    scope_stack: Vec<scope::Scope>,
-    lexer: Lexer<'a>
+    scope_stack: Vec<Scope>,
+    tokens: TokenBuffer<'a>,
+    strict: bool,
+    // `in` is not an operator in the init of a for statement
+    allow_in: bool,
+    // `return` is only allowed in a function body
+    in_function: bool,
+    // `await` and `yield` are operators in async functions and generators
+    in_async: bool,
+    in_generator: bool,
+    // `super.x` and `super[x]` refer to the home object of a method
+    in_method: bool,
+    // `new.target` is the constructor a function was called with, arrows see the one around them
+    new_target: bool,
+    // the labels around the current statement, innermost last
+    labels: Vec<Label>,
+    // enclosing loops, and switches where only `break` is allowed
+    loop_depth: u32,
+    breakable_depth: u32,
+    // the last consumed token, errors point at it
+    last_span: Span,
+    // errors in the array or object literal being parsed that wait on whether it is a pattern
+    cover: Cover,
+    // `(a)` or `async(a)` read at the start of an assignment expression that turned out
+    // not to be arrow parameters, the expression continues from it
+    primary: Option<Expression>
+}
+// `continue l` needs l to label a loop
+struct Label {
+    name: Atom,
+    is_loop: bool
+}
+// an array or object literal is only known to be a destructuring pattern once `=` follows,
+// `{ a = 1 }` is an error unless it is one and `[...a, ]` is an error if it is one.
+// the first error of each kind is kept until the literal is decided
+#[derive(Default)]
+struct Cover {
+    expression: Option<error::ParseError>,
+    pattern: Option<error::ParseError>
+}
+
+impl Cover {
+    fn merge(&mut self, other: Cover) {
+        self.expression = self.expression.take().or(other.expression);
+        self.pattern = self.pattern.take().or(other.pattern);
+    }
 }
 
 impl<'a> Parser<'a> {
     pub fn new(lexer: Lexer<'a>) -> Self {
-        Self { scope_stack: vec![], lexer: lexer }
+        Self {
+            scope_stack: vec![Scope::new(ScopeType::ScopeTop)],
+            tokens: TokenBuffer::new(lexer),
+            strict: false,
+            allow_in: true,
+            in_function: false,
+            in_async: false,
+            in_generator: false,
+            in_method: false,
+            new_target: false,
+            labels: vec![],
+            loop_depth: 0,
+            breakable_depth: 0,
+            last_span: Span::default(),
+            cover: Cover::default(),
+            primary: None
+        }
+    }
+    // parse the whole source as strict mode code, like a module
+    pub fn strict_mode(&mut self, strict: bool) {
+        self.strict = strict;
     }
-    fn next(&mut self) -> Result<Token, error::ParseError> {
-        self.lexer.advance().map_err(|e| error::ParseError::LexerError(e))
+    fn peek(&mut self) -> Result<Option<&Token<'a>>, error::ParseError> {
+        self.peek_nth(0)
     }
-    fn next_check(&mut self, tp: TokenMatcher) -> Result<Token, error::ParseError> {
+    fn peek_nth(&mut self, n: usize) -> Result<Option<&Token<'a>>, error::ParseError> {
+        self.tokens.peek(n).map_err(error::ParseError::from)
+    }
+    fn next(&mut self) -> Result<Token<'a>, error::ParseError> {
+        match self.tokens.next().map_err(error::ParseError::from)? {
+            Some(t) => {
+                self.last_span = t.span;
+                Ok(t)
+            }
+            None => Err(self.error(error::ParseErrorKind::UnexpectedEnd))
+        }
+    }
+    // an error at the last consumed token, or right after it at the end of input
+    fn error(&self, kind: error::ParseErrorKind) -> error::ParseError {
+        let span = match kind {
+            error::ParseErrorKind::UnexpectedEnd => Span { start: self.last_span.end, end: self.last_span.end },
+            _ => self.last_span
+        };
+        self.error_at(kind, span)
+    }
+    fn error_at(&self, kind: error::ParseErrorKind, span: Span) -> error::ParseError {
+        error::ParseError {
+            kind,
+            span: Box::new(span),
+            source_line: self.tokens.line_text(span.start.offset).to_string()
+        }
+    }
+    fn peek_category(&mut self) -> Result<Option<TokenType>, error::ParseError> {
+        Ok(self.peek()?.map(|t| t.category))
+    }
+    fn peek_is(&mut self, tp: TokenType) -> Result<bool, error::ParseError> {
+        Ok(self.peek()?.is_some_and(|t| t.category == tp))
+    }
+    // automatic semicolon insertion, a `;` is consumed if present, otherwise one is inserted
+    // before a `}`, at the end of input, or before a token on a new line
+    fn consume_semicolon(&mut self) -> Result<(), error::ParseError> {
+        match self.peek()? {
+            None => Ok(()),
+            Some(t) if t.category == TokenType::SEMICOLON => {
+                self.next()?;
+                Ok(())
+            }
+            Some(t) if t.category == TokenType::RBRACE || t.newline_before => Ok(()),
+            Some(_) => {
+                let t = self.next()?;
+                Err(self.error(error::ParseErrorKind::UnexpectedToken(t.value.into_owned())))
+            }
+        }
+    }
+    // for restricted productions like `return [no LineTerminator here] Expression`
+    // and postfix `++`/`--`, a line break ends the production
+    fn newline_before_next(&mut self) -> Result<bool, error::ParseError> {
+        match self.peek()? {
+            Some(t) => Ok(t.newline_before),
+            None => Ok(true)
+        }
+    }
+    // an identifier used as a binding or reference can't be a reserved word,
+    // written with escapes or not
+    fn check_identifier(&self, t: &Token) -> Result<(), error::ParseError> {
+        if t.is_escaped_reserved_word() {
+            return Err(self.error_at(error::ParseErrorKind::EscapedReservedWord(t.value.to_string()), t.span));
+        }
+        if self.strict && t.keyword.is_some_and(is_strict_mode_reserved) {
+            return Err(self.error_at(error::ParseErrorKind::StrictModeReservedWord(t.value.to_string()), t.span));
+        }
+        if (self.in_async && t.is_contextual(TokenType::AWAIT)) || (self.in_generator && t.is_contextual(TokenType::YIELD)) {
+            return Err(self.error_at(error::ParseErrorKind::UnexpectedToken(t.value.to_string()), t.span));
+        }
+        Ok(())
+    }
+    // `eval` and `arguments` can't be bound in strict mode code
+    fn check_binding_name(&self, name: Atom) -> Result<(), error::ParseError> {
+        if self.strict && (name == Atom::EVAL || name == Atom::ARGUMENTS) {
+            return Err(self.error(error::ParseErrorKind::StrictModeAssignment(name.to_string())));
+        }
+        Ok(())
+    }
+    // let and const names are lexical to the current scope, var names are
+    // hoisted through the enclosing blocks up to the function or top level scope
+    fn declare_names(&mut self, names: &[Atom], var: bool) -> Result<(), error::ParseError> {
+        for &name in names {
+            self.check_binding_name(name)?;
+            if !var {
+                if let Some(scope) = self.scope_stack.last_mut() {
+                    scope.declare(name).map_err(|k| self.error(k))?;
+                }
+                continue;
+            }
+            for i in (0..self.scope_stack.len()).rev() {
+                self.scope_stack[i].declare_var(name).map_err(|k| self.error(k))?;
+                if self.scope_stack[i].is_var_scope() {
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+    fn next_check(&mut self, tp: TokenMatcher) -> Result<Token<'a>, error::ParseError> {
         let n = self.next()?;
         match tp {
             TokenMatcher::Single(stp) => {
-                if n.category == n.category {
+                if n.category == stp {
                     return Ok(n);
                 }
-                Err(error::ParseError::UnexpectedToken(n.value))
+                Err(self.error(error::ParseErrorKind::UnexpectedToken(n.value.into_owned())))
             }
             TokenMatcher::List(stp) => {
-                if stp.iter().any(|x| *x == n.category) {
+                if stp.contains(&n.category) {
                     return Ok(n);
                 }
-                Err(error::ParseError::UnexpectedToken(n.value))
+                Err(self.error(error::ParseErrorKind::UnexpectedToken(n.value.into_owned())))
             }
         }
      
     }
-    pub fn parse(&mut self) -> Result<(), error::ParseError> {
-        let nt = self.lexer.advance();
-        match nt {
-            Ok(tt) => {
-                match tt.category {
-                    TokenType::LET => {
-                        let t = self.parse_decl();
-                        println!("{:#?}", t);
-                    }
-                    _ => ()
-                }
-            }
-            Err(e) => {}
+    // runs f with `in` allowed or not as an operator, restoring the old value after
+    fn with_in<T>(&mut self, allow_in: bool, f: impl FnOnce(&mut Self) -> Result<T, error::ParseError>) -> Result<T, error::ParseError> {
+        let saved = self.allow_in;
+        self.allow_in = allow_in;
+        let result = f(self);
+        self.allow_in = saved;
+        result
+    }
+    // run f with nothing in the cover, and return what it left there
+    fn with_cover<T>(&mut self, f: impl FnOnce(&mut Self) -> Result<T, error::ParseError>) -> Result<(T, Cover), error::ParseError> {
+        let outer = std::mem::take(&mut self.cover);
+        let result = f(self);
+        let inner = std::mem::replace(&mut self.cover, outer);
+        Ok((result?, inner))
+    }
+    // remember an error for when the literal turns out an expression
+    fn cover_expression_error(&mut self, kind: error::ParseErrorKind) {
+        if self.cover.expression.is_none() {
+            self.cover.expression = Some(self.error(kind));
         }
-    
-        Ok(())
     }
-    fn parse_decl(&mut self) -> Result<VariableDeclaration, error::ParseError> {
-        let ident = self.next_check(TokenMatcher::from(TokenType::Identifier))?;
-        
-        self.next_check(TokenMatcher::from(TokenType::ASSIGN))?;
-        let exp = self.parse_literal()?;
-        let mut decls = vec![];
-        decls.push(Variable::new(ident.value, Some(exp)));
-        Ok(VariableDeclaration {
-            list: decls
-        })
-    }
-    fn parse_literal(&mut self) -> Result<Expression, error::ParseError>{
-        let n = self.next()?;
-        match n.category {
-            TokenType::STRING_LITERAL => {
-                let lt = Literal::from(n.value);
-                return Ok(Expression::from(lt));
-            }
-            TokenType::NULL_LITERAL => {
-                return Ok(Expression::from(Literal::Null));
-            }
-            TokenType::NUMERIC_LITERAL => {
-                return Ok(Expression::from(Literal::from(n.value)));
-            }
-            TokenType::TRUE_LITERAL | TokenType::FALSE_LITERAL => {
-                return Ok(Expression::from(Literal::from(n.value == "true")));
-            }
-            _ => {
-                return Err(error::ParseError::UnexpectedToken(n.value));
-            }
+    // remember an error for when the literal turns out a pattern
+    fn cover_pattern_error(&mut self, kind: error::ParseErrorKind) {
+        if self.cover.pattern.is_none() {
+            self.cover.pattern = Some(self.error(kind));
         }
     }
-    fn parse_unary(&mut self) -> Result<Expression, error::ParseError> {
-        let nt = self.next_check(TokenMatcher::from(vec![
-            TokenType::ADD,
-            TokenType::SUB,
-            TokenType::Bang
-        ]))?;
+    pub fn parse(&mut self) -> Result<Program, error::ParseError> {
+        self.parse_program()
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,170 @@
+use crate::atom::Atom;
+use crate::lexer::token::{TokenType, TokenMatcher};
+use crate::ast::expression::*;
+use crate::ast::variable::Identifier;
+use super::Parser;
+use super::error::{ParseError, ParseErrorKind};
+
+impl<'a> Parser<'a> {
+    // `[a, , ...b,]`, a trailing comma is not a hole.
+    // the elements may be read again as a pattern, so they are parsed as covers
+    pub(super) fn parse_array(&mut self) -> Result<Expression, ParseError> {
+        self.next_check(TokenMatcher::from(TokenType::LBRACK))?;
+        let mut elements = vec![];
+        loop {
+            match self.peek_category()? {
+                Some(TokenType::RBRACK) => break,
+                Some(TokenType::COMMA) => {
+                    self.next()?;
+                    elements.push(None);
+                    continue;
+                }
+                Some(TokenType::ELLIPSIS) => {
+                    self.next()?;
+                    let argument = self.with_in(true, |p| p.parse_assignment_cover())?;
+                    elements.push(Some(Expression::Spread(SpreadElement { argument: Box::new(argument) })));
+                    // `[...a, b] = c`, the rest has to come last
+                    if self.peek_is(TokenType::COMMA)? {
+                        self.cover_pattern_error(ParseErrorKind::InvalidAssignmentTarget);
+                    }
+                }
+                _ => elements.push(Some(self.with_in(true, |p| p.parse_assignment_cover())?))
+            }
+            if !self.peek_is(TokenType::RBRACK)? {
+                self.next_check(TokenMatcher::from(TokenType::COMMA))?;
+            }
+        }
+        self.next_check(TokenMatcher::from(TokenType::RBRACK))?;
+        Ok(Expression::Array(ArrayExpr { elements }))
+    }
+    // `{ a, b: 1, [c]: 2, d() {}, get e() {}, ...f }`.
+    // `__proto__: x` sets the prototype, so it may only appear once, unless the object is a pattern
+    pub(super) fn parse_object(&mut self) -> Result<Expression, ParseError> {
+        self.next_check(TokenMatcher::from(TokenType::LBRACE))?;
+        let mut properties = vec![];
+        let mut has_proto = false;
+        while !self.peek_is(TokenType::RBRACE)? {
+            let member = self.parse_object_member()?;
+            // `{ ...a, b } = c`, the rest has to come last
+            if matches!(member, ObjectMember::Spread(_)) && self.peek_is(TokenType::COMMA)? {
+                self.cover_pattern_error(ParseErrorKind::InvalidAssignmentTarget);
+            }
+            if let ObjectMember::Property(p) = &member {
+                if is_proto_setter(p) {
+                    if has_proto {
+                        self.cover_expression_error(ParseErrorKind::DuplicateProto);
+                    }
+                    has_proto = true;
+                }
+            }
+            properties.push(member);
+            if !self.peek_is(TokenType::RBRACE)? {
+                self.next_check(TokenMatcher::from(TokenType::COMMA))?;
+            }
+        }
+        self.next_check(TokenMatcher::from(TokenType::RBRACE))?;
+        Ok(Expression::Object(ObjectExpr { properties }))
+    }
+    fn parse_object_member(&mut self) -> Result<ObjectMember, ParseError> {
+        if self.peek_is(TokenType::ELLIPSIS)? {
+            self.next()?;
+            let argument = self.with_in(true, |p| p.parse_assignment_cover())?;
+            return Ok(ObjectMember::Spread(SpreadElement { argument: Box::new(argument) }));
+        }
+        // `{ a }`, a name that is also the value
+        let shorthand = self.peek_is(TokenType::Identifier)? && matches!(
+            self.peek_nth(1)?.map(|t| t.category),
+            Some(TokenType::COMMA | TokenType::RBRACE | TokenType::ASSIGN)
+        );
+        if shorthand {
+            let t = self.next()?;
+            self.check_identifier(&t)?;
+            let mut value = Expression::Identifier(Identifier::new(t.name()));
+            // `{ a = 1 }` is only valid as a destructuring pattern
+            if self.peek_is(TokenType::ASSIGN)? {
+                self.cover_expression_error(ParseErrorKind::ShorthandInitializer);
+                let left = self.to_simple_target(value)?;
+                self.next()?;
+                let right = self.with_in(true, |p| p.parse_assignment())?;
+                value = Expression::Assign(AssignExpr {
+                    operator: AssignOp::Assign,
+                    left: Box::new(left),
+                    right: Box::new(right)
+                });
+            }
+            return Ok(ObjectMember::Property(Property {
+                key: PropertyKey::Identifier(Identifier::new(t.name())),
+                value,
+                kind: PropertyKind::Init,
+                method: false,
+                shorthand: true
+            }));
+        }
+        let (is_async, generator, kind) = self.parse_method_modifiers()?;
+        let key = self.parse_property_key()?;
+        if is_async || generator || kind != PropertyKind::Init || self.peek_is(TokenType::LPAREN)? {
+            let function = self.parse_method(is_async, generator, kind)?;
+            return Ok(ObjectMember::Property(Property {
+                key,
+                value: Expression::Function(Box::new(function)),
+                kind,
+                method: kind == PropertyKind::Init,
+                shorthand: false
+            }));
+        }
+        self.next_check(TokenMatcher::from(TokenType::COLON))?;
+        let value = self.with_in(true, |p| p.parse_assignment_cover())?;
+        Ok(ObjectMember::Property(Property {
+            key,
+            value,
+            kind: PropertyKind::Init,
+            method: false,
+            shorthand: false
+        }))
+    }
+    // `async`, `*`, `get` or `set` before a method name. each of the words is
+    // a property name itself when no key follows, like in `{ get: 1 }` or `{ async() {} }`
+    fn parse_method_modifiers(&mut self) -> Result<(bool, bool, PropertyKind), ParseError> {
+        let is_async = self.peek()?.is_some_and(|t| t.is_contextual(TokenType::ASYNC)) &&
+            self.peek_nth(1)?.is_some_and(|t| !t.newline_before) && self.key_follows(1)?;
+        if is_async {
+            self.next()?;
+        }
+        let generator = self.peek_is(TokenType::MUL)?;
+        if generator {
+            self.next()?;
+        }
+        if is_async || generator {
+            return Ok((is_async, generator, PropertyKind::Init));
+        }
+        let kind = match self.peek()? {
+            Some(t) if t.is_contextual(TokenType::GET) => PropertyKind::Get,
+            Some(t) if t.is_contextual(TokenType::SET) => PropertyKind::Set,
+            _ => return Ok((false, false, PropertyKind::Init))
+        };
+        if !self.key_follows(1)? {
+            return Ok((false, false, PropertyKind::Init));
+        }
+        self.next()?;
+        Ok((false, false, kind))
+    }
+    // whether the nth token can start a property key rather than end the name before it
+    fn key_follows(&mut self, n: usize) -> Result<bool, ParseError> {
+        Ok(!matches!(
+            self.peek_nth(n)?.map(|t| t.category),
+            None | Some(TokenType::COMMA | TokenType::COLON | TokenType::LPAREN | TokenType::RBRACE | TokenType::ASSIGN)
+        ))
+    }
+}
+
+// `__proto__: x` or `"__proto__": x`, not shorthand, computed or a method
+fn is_proto_setter(p: &Property) -> bool {
+    if p.shorthand || p.method || p.kind != PropertyKind::Init {
+        return false;
+    }
+    match &p.key {
+        PropertyKey::Identifier(id) => id.value == Atom::PROTO,
+        PropertyKey::String(name) => *name == Atom::PROTO,
+        _ => false
+    }
+}
@@ -0,0 +1,214 @@
+use crate::lexer::token::{TokenType, TokenMatcher};
+use crate::ast::expression::{Expression, ArrayExpr, ObjectExpr, ObjectMember, PropertyKey, PropertyKind, AssignOp};
+use crate::ast::variable::*;
+use super::{Parser, Cover};
+use super::expr::is_literal;
+use super::error::{ParseError, ParseErrorKind};
+
+impl<'a> Parser<'a> {
+    // `a`, `[a, b]` or `{ a, b }`
+    pub(super) fn parse_binding_target(&mut self) -> Result<Pattern, ParseError> {
+        match self.peek_category()? {
+            Some(TokenType::LBRACK) => self.parse_array_pattern(),
+            Some(TokenType::LBRACE) => self.parse_object_pattern(),
+            _ => Ok(Pattern::Identifier(self.parse_binding_identifier()?))
+        }
+    }
+    fn parse_binding_identifier(&mut self) -> Result<Identifier, ParseError> {
+        let t = self.next_check(TokenMatcher::from(TokenType::Identifier))?;
+        self.check_identifier(&t)?;
+        Ok(Identifier::new(t.name()))
+    }
+    // a target with an optional default, `a = 1` or `[a] = []`
+    pub(super) fn parse_binding_element(&mut self) -> Result<Pattern, ParseError> {
+        let target = self.parse_binding_target()?;
+        self.parse_pattern_default(target)
+    }
+    fn parse_pattern_default(&mut self, target: Pattern) -> Result<Pattern, ParseError> {
+        if !self.peek_is(TokenType::ASSIGN)? {
+            return Ok(target);
+        }
+        self.next()?;
+        let right = self.with_in(true, |p| p.parse_assignment())?;
+        Ok(Pattern::Assign(AssignPattern {
+            left: Box::new(target),
+            right: Box::new(right)
+        }))
+    }
+    // `[a, , b = 1, ...c]`, a trailing comma is not a hole and the rest comes last
+    fn parse_array_pattern(&mut self) -> Result<Pattern, ParseError> {
+        self.next_check(TokenMatcher::from(TokenType::LBRACK))?;
+        let mut elements = vec![];
+        let mut rest = None;
+        loop {
+            match self.peek_category()? {
+                Some(TokenType::RBRACK) => break,
+                Some(TokenType::COMMA) => {
+                    self.next()?;
+                    elements.push(None);
+                    continue;
+                }
+                Some(TokenType::ELLIPSIS) => {
+                    self.next()?;
+                    rest = Some(Box::new(self.parse_binding_target()?));
+                    break;
+                }
+                _ => elements.push(Some(self.parse_binding_element()?))
+            }
+            if !self.peek_is(TokenType::RBRACK)? {
+                self.next_check(TokenMatcher::from(TokenType::COMMA))?;
+            }
+        }
+        self.next_check(TokenMatcher::from(TokenType::RBRACK))?;
+        Ok(Pattern::Array(ArrayPattern {
+            elements,
+            rest
+        }))
+    }
+    // `{ a, b: c, [d]: e = 1, ...f }`, the rest is a plain name and comes last
+    fn parse_object_pattern(&mut self) -> Result<Pattern, ParseError> {
+        self.next_check(TokenMatcher::from(TokenType::LBRACE))?;
+        let mut properties = vec![];
+        let mut rest = None;
+        while !self.peek_is(TokenType::RBRACE)? {
+            if self.peek_is(TokenType::ELLIPSIS)? {
+                self.next()?;
+                rest = Some(Box::new(Pattern::Identifier(self.parse_binding_identifier()?)));
+                break;
+            }
+            properties.push(self.parse_pattern_property()?);
+            if !self.peek_is(TokenType::RBRACE)? {
+                self.next_check(TokenMatcher::from(TokenType::COMMA))?;
+            }
+        }
+        self.next_check(TokenMatcher::from(TokenType::RBRACE))?;
+        Ok(Pattern::Object(ObjectPattern {
+            properties,
+            rest
+        }))
+    }
+    // a shorthand `{ a }` or `{ a = 1 }` has to be a binding identifier,
+    // `{ if: a }` only needs a property name
+    fn parse_pattern_property(&mut self) -> Result<PatternProperty, ParseError> {
+        let shorthand = self.peek_is(TokenType::Identifier)? &&
+            !self.peek_nth(1)?.is_some_and(|t| t.category == TokenType::COLON);
+        if shorthand {
+            let id = self.parse_binding_identifier()?;
+            let key = PropertyKey::Identifier(Identifier::new(id.value));
+            let value = self.parse_pattern_default(Pattern::Identifier(id))?;
+            return Ok(PatternProperty {
+                key,
+                value,
+                shorthand: true
+            });
+        }
+        let key = self.parse_property_key()?;
+        self.next_check(TokenMatcher::from(TokenType::COLON))?;
+        let value = self.parse_binding_element()?;
+        Ok(PatternProperty {
+            key,
+            value,
+            shorthand: false
+        })
+    }
+    // the left side of `=`, or of `in` and `of` in a for head. an array or object
+    // literal is read again as a destructuring pattern, anything else has to be
+    // a name or member access
+    pub(super) fn to_assignment_target(&self, e: Expression, cover: Cover) -> Result<Pattern, ParseError> {
+        if is_literal(&e) {
+            if let Some(err) = cover.pattern {
+                return Err(err);
+            }
+            return self.to_pattern(e);
+        }
+        if let Some(err) = cover.expression {
+            return Err(err);
+        }
+        self.to_simple_target(e)
+    }
+    // a name or member access, parentheses around it don't matter
+    pub(super) fn to_simple_target(&self, e: Expression) -> Result<Pattern, ParseError> {
+        self.check_simple_target(&e)?;
+        let mut e = e;
+        while let Expression::Paren(inner) = e {
+            e = *inner;
+        }
+        match e {
+            Expression::Identifier(id) => Ok(Pattern::Identifier(id)),
+            e => Ok(Pattern::Member(Box::new(e)))
+        }
+    }
+    // an element of arrow parameters read again as a binding, a name or a literal
+    // read as a pattern, with a default or not. there is nothing to assign to in it
+    pub(super) fn to_binding(&self, e: Expression) -> Result<Pattern, ParseError> {
+        let pattern = match e {
+            Expression::Identifier(id) => Pattern::Identifier(id),
+            e @ (Expression::Array(_) | Expression::Object(_) | Expression::Assign(_)) => self.to_pattern(e)?,
+            _ => return Err(self.error(ParseErrorKind::InvalidArrowParameter))
+        };
+        if !pattern.is_binding() {
+            return Err(self.error(ParseErrorKind::InvalidArrowParameter));
+        }
+        Ok(pattern)
+    }
+    // an element of a literal that is read as a pattern. `a = 1` in it is a default,
+    // its left side was made a target when it was parsed
+    fn to_pattern(&self, e: Expression) -> Result<Pattern, ParseError> {
+        match e {
+            Expression::Array(arr) => self.array_to_pattern(arr),
+            Expression::Object(obj) => self.object_to_pattern(obj),
+            Expression::Assign(a) if a.operator == AssignOp::Assign => Ok(Pattern::Assign(AssignPattern {
+                left: a.left,
+                right: a.right
+            })),
+            e => self.to_simple_target(e)
+        }
+    }
+    // `[a, , ...b]`, the spread is the rest and has no default
+    fn array_to_pattern(&self, arr: ArrayExpr) -> Result<Pattern, ParseError> {
+        let count = arr.elements.len();
+        let mut elements = vec![];
+        let mut rest = None;
+        for (i, element) in arr.elements.into_iter().enumerate() {
+            match element {
+                None => elements.push(None),
+                Some(Expression::Spread(s)) => {
+                    if i + 1 != count || matches!(*s.argument, Expression::Assign(_)) {
+                        return Err(self.error(ParseErrorKind::InvalidAssignmentTarget));
+                    }
+                    rest = Some(Box::new(self.to_pattern(*s.argument)?));
+                }
+                Some(e) => elements.push(Some(self.to_pattern(e)?))
+            }
+        }
+        Ok(Pattern::Array(ArrayPattern {
+            elements,
+            rest
+        }))
+    }
+    // `{ a, b: c.d, e = 1, ...f }`, no methods or accessors, and the rest is a plain target
+    fn object_to_pattern(&self, obj: ObjectExpr) -> Result<Pattern, ParseError> {
+        let count = obj.properties.len();
+        let mut properties = vec![];
+        let mut rest = None;
+        for (i, member) in obj.properties.into_iter().enumerate() {
+            match member {
+                ObjectMember::Spread(s) if i + 1 == count => {
+                    rest = Some(Box::new(self.to_simple_target(*s.argument)?));
+                }
+                ObjectMember::Property(p) if p.kind == PropertyKind::Init && !p.method => {
+                    properties.push(PatternProperty {
+                        key: p.key,
+                        value: self.to_pattern(p.value)?,
+                        shorthand: p.shorthand
+                    });
+                }
+                _ => return Err(self.error(ParseErrorKind::InvalidAssignmentTarget))
+            }
+        }
+        Ok(Pattern::Object(ObjectPattern {
+            properties,
+            rest
+        }))
+    }
+}
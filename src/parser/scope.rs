@@ -1,28 +1,51 @@
-use super::error::ParseError;
+use super::error::ParseErrorKind;
+use crate::atom::Atom;
 
+#[allow(clippy::enum_variant_names)]
+#[derive(Clone, Copy, PartialEq)]
 pub enum ScopeType {
     ScopeTop = 1,
     ScopeFunction = 2,
+    ScopeBlock = 4
 }
 
+// names declared with let, const and catch parameters are lexical.
+// var_list has the var names hoisted through the scope, a lexical declaration
+// of the same name in it is an error
 pub struct Scope {
-    var_list: Vec<String>,
+    var_list: Vec<Atom>,
+    lexical_list: Vec<Atom>,
     type_flag: u8
 }
 
 impl Scope {
-    pub fn new() -> Self {
+    pub fn new(tp: ScopeType) -> Self {
         Self {
             var_list: vec![],
-            type_flag: 1
+            lexical_list: vec![],
+            type_flag: tp as u8
         }
     }
-    pub fn declare(&mut self, name: &str) -> Result<(), ParseError> {
-        if self.var_list.iter().any(|x| x == name) {
-            Err(ParseError::AlreadyDeclared(String::from(name)))
+    // var declarations stop at a function or the top level
+    pub fn is_var_scope(&self) -> bool {
+        self.type_flag & (ScopeType::ScopeTop as u8 | ScopeType::ScopeFunction as u8) != 0
+    }
+    pub fn declare(&mut self, name: Atom) -> Result<(), ParseErrorKind> {
+        if self.lexical_list.contains(&name) || self.var_list.contains(&name) {
+            Err(ParseErrorKind::AlreadyDeclared(name.to_string()))
         } else {
-            self.var_list.push(name.to_string());
+            self.lexical_list.push(name);
             Ok(())
         }
     }
-}
\ No newline at end of file
+    // also used for a plain catch parameter, which `var` may redeclare but `let` may not
+    pub fn declare_var(&mut self, name: Atom) -> Result<(), ParseErrorKind> {
+        if self.lexical_list.contains(&name) {
+            return Err(ParseErrorKind::AlreadyDeclared(name.to_string()));
+        }
+        if !self.var_list.contains(&name) {
+            self.var_list.push(name);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,779 @@
+use crate::atom::Atom;
+use crate::lexer::token::{TokenType, TokenMatcher};
+use crate::ast::expression::{Expression, Literal};
+use crate::ast::statement::*;
+use crate::ast::variable::{Identifier, VariableDeclaration, VariableKind, Variable, Pattern};
+use super::{Parser, Label};
+use super::error::{ParseError, ParseErrorKind};
+use super::scope::{Scope, ScopeType};
+
+impl<'a> Parser<'a> {
+    pub fn parse_program(&mut self) -> Result<Program, ParseError> {
+        let (body, _) = self.parse_body()?;
+        if self.peek()?.is_some() {
+            let t = self.next()?;
+            return Err(self.error(ParseErrorKind::UnexpectedToken(t.value.into_owned())));
+        }
+        Ok(Program {
+            body,
+            strict: self.strict
+        })
+    }
+    // a script or function body, up to the end of input or a `}` left for the caller.
+    // the directive prologue comes first, a "use strict" in it makes the rest strict
+    // and is reported with the statements
+    pub(super) fn parse_body(&mut self) -> Result<(Vec<Statement>, bool), ParseError> {
+        let mut body = vec![];
+        let mut prologue = true;
+        let mut has_use_strict = false;
+        // `"\07"; "use strict"`, a directive before is only checked once the body turns strict
+        let mut octal_escape = None;
+        while !matches!(self.peek_category()?, None | Some(TokenType::RBRACE)) {
+            // without escapes, the value is the source text between the quotes
+            let (use_strict, octal) = match self.peek()? {
+                Some(t) if t.category == TokenType::STRING_LITERAL => {
+                    (t.string.is_none() && t.value == "use strict", t.octal_escape.then(|| (t.value.to_string(), t.span)))
+                }
+                _ => {
+                    prologue = false;
+                    (false, None)
+                }
+            };
+            let statement = self.parse_statement_list_item()?;
+            if prologue {
+                match &statement {
+                    Statement::Expression(ExpressionStatement { expression: Expression::Literal(Literal::String(_)) }) => {
+                        if use_strict {
+                            // reported at the string with the escape, not at the directive
+                            if let Some((value, span)) = octal_escape.take() {
+                                return Err(self.error_at(ParseErrorKind::OctalEscapeInStrictMode(value), span));
+                            }
+                            self.strict = true;
+                            has_use_strict = true;
+                        }
+                        octal_escape = octal_escape.or(octal);
+                    }
+                    _ => prologue = false
+                }
+            }
+            body.push(statement);
+        }
+        Ok((body, has_use_strict))
+    }
+    pub fn parse_statement(&mut self) -> Result<Statement, ParseError> {
+        let tp = match self.peek_category()? {
+            Some(tp) => tp,
+            None => return Err(self.error(ParseErrorKind::UnexpectedEnd))
+        };
+        match tp {
+            TokenType::LBRACE => Ok(Statement::Block(self.parse_block()?)),
+            TokenType::SEMICOLON => {
+                self.next()?;
+                Ok(Statement::Empty)
+            }
+            TokenType::IF => self.parse_if(),
+            TokenType::WHILE => self.parse_while(),
+            TokenType::DO => self.parse_do_while(),
+            TokenType::FOR => self.parse_for(),
+            TokenType::SWITCH => self.parse_switch(),
+            TokenType::BREAK => self.parse_break(),
+            TokenType::CONTINUE => self.parse_continue(),
+            TokenType::RETURN => self.parse_return(),
+            TokenType::THROW => self.parse_throw(),
+            TokenType::TRY => self.parse_try(),
+            TokenType::DEBUGGER => {
+                self.next()?;
+                self.consume_semicolon()?;
+                Ok(Statement::Debugger)
+            }
+            TokenType::WITH => self.parse_with(),
+            TokenType::VAR => {
+                let decl = self.parse_decl(VariableKind::Var)?;
+                self.check_initializers(&decl)?;
+                self.consume_semicolon()?;
+                Ok(Statement::VariableDeclaration(decl))
+            }
+            // `let [` can't start an expression statement, `let` alone may
+            TokenType::Identifier if self.peek()?.is_some_and(|t| t.is_contextual(TokenType::LET)) &&
+                self.peek_nth(1)?.is_some_and(|t| t.category == TokenType::LBRACK) => {
+                let t = self.next()?;
+                Err(self.error(ParseErrorKind::UnexpectedToken(t.value.into_owned())))
+            }
+            TokenType::Identifier if self.peek_nth(1)?.is_some_and(|t| t.category == TokenType::COLON) => {
+                self.parse_labeled()
+            }
+            // a function declaration is only allowed where a declaration is,
+            // an async one included
+            _ if self.at_function()? => {
+                let t = self.next()?;
+                Err(self.error(ParseErrorKind::UnexpectedToken(t.value.into_owned())))
+            }
+            _ => {
+                let expression = self.parse_expression()?;
+                self.consume_semicolon()?;
+                Ok(Statement::Expression(ExpressionStatement { expression }))
+            }
+        }
+    }
+    // declarations are only allowed directly in a block, case or script,
+    // `if (a) let b = 1` is an error
+    fn parse_statement_list_item(&mut self) -> Result<Statement, ParseError> {
+        if self.at_function()? {
+            return Ok(Statement::Function(self.parse_function(true)?));
+        }
+        match self.declaration_kind()? {
+            Some(kind) if kind != VariableKind::Var => {
+                let decl = self.parse_decl(kind)?;
+                self.check_initializers(&decl)?;
+                self.consume_semicolon()?;
+                Ok(Statement::VariableDeclaration(decl))
+            }
+            _ => self.parse_statement()
+        }
+    }
+    // `let` followed by a binding starts a declaration, otherwise it is an identifier
+    fn declaration_kind(&mut self) -> Result<Option<VariableKind>, ParseError> {
+        let (tp, is_let) = match self.peek()? {
+            Some(t) => (t.category, t.is_contextual(TokenType::LET)),
+            None => return Ok(None)
+        };
+        match tp {
+            TokenType::VAR => Ok(Some(VariableKind::Var)),
+            TokenType::CONST => Ok(Some(VariableKind::Const)),
+            _ if is_let => {
+                let binding = self.peek_nth(1)?.is_some_and(|t| {
+                    matches!(t.category, TokenType::Identifier | TokenType::LBRACK | TokenType::LBRACE)
+                });
+                Ok(if binding { Some(VariableKind::Let) } else { None })
+            }
+            _ => Ok(None)
+        }
+    }
+    // `var a, b = 1`, `let [a, b] = c`, `const { a } = b`. initializers are left
+    // to the caller, the left side of for-in and for-of has none
+    fn parse_decl(&mut self, kind: VariableKind) -> Result<VariableDeclaration, ParseError> {
+        self.next()?;
+        let mut list = vec![];
+        loop {
+            let id = self.parse_binding_target()?;
+            let mut names = vec![];
+            id.bound_names(&mut names);
+            if kind != VariableKind::Var && names.contains(&Atom::LET) {
+                return Err(self.error(ParseErrorKind::LetInLexicalDeclaration));
+            }
+            self.declare_names(&names, kind == VariableKind::Var)?;
+            let init = if self.peek_is(TokenType::ASSIGN)? {
+                self.next()?;
+                Some(self.parse_assignment()?)
+            } else {
+                None
+            };
+            list.push(Variable::new(id, init));
+            if !self.peek_is(TokenType::COMMA)? {
+                break;
+            }
+            self.next()?;
+        }
+        Ok(VariableDeclaration {
+            kind,
+            list
+        })
+    }
+    // a const or a destructuring needs an initializer, `let a` and `var a` don't
+    fn check_initializers(&self, decl: &VariableDeclaration) -> Result<(), ParseError> {
+        for v in &decl.list {
+            if v.init.is_some() {
+                continue;
+            }
+            if decl.kind == VariableKind::Const {
+                return Err(self.error(ParseErrorKind::MissingConstInitializer));
+            }
+            if !matches!(v.id, Pattern::Identifier(_)) {
+                return Err(self.error(ParseErrorKind::MissingDestructuringInitializer));
+            }
+        }
+        Ok(())
+    }
+    // a block has its own scope for lexical declarations
+    fn parse_block(&mut self) -> Result<BlockStatement, ParseError> {
+        self.next_check(TokenMatcher::from(TokenType::LBRACE))?;
+        self.scope_stack.push(Scope::new(ScopeType::ScopeBlock));
+        let body = self.parse_statement_list();
+        self.scope_stack.pop();
+        let body = body?;
+        self.next_check(TokenMatcher::from(TokenType::RBRACE))?;
+        Ok(BlockStatement { body })
+    }
+    // statements up to a `}`, which is left for the caller
+    fn parse_statement_list(&mut self) -> Result<Vec<Statement>, ParseError> {
+        let mut body = vec![];
+        while !self.peek_is(TokenType::RBRACE)? {
+            body.push(self.parse_statement_list_item()?);
+        }
+        Ok(body)
+    }
+    // `( Expression )` after if, while, switch and with
+    fn parse_paren_expression(&mut self) -> Result<Expression, ParseError> {
+        self.next_check(TokenMatcher::from(TokenType::LPAREN))?;
+        let expression = self.parse_expression()?;
+        self.next_check(TokenMatcher::from(TokenType::RPAREN))?;
+        Ok(expression)
+    }
+    fn parse_if(&mut self) -> Result<Statement, ParseError> {
+        self.next()?;
+        let test = self.parse_paren_expression()?;
+        let consequent = self.parse_statement()?;
+        let alternate = if self.peek_is(TokenType::ELSE)? {
+            self.next()?;
+            Some(Box::new(self.parse_statement()?))
+        } else {
+            None
+        };
+        Ok(Statement::If(IfStatement {
+            test,
+            consequent: Box::new(consequent),
+            alternate
+        }))
+    }
+    // the body of a loop, where `break` and `continue` are allowed
+    fn parse_loop_body(&mut self) -> Result<Box<Statement>, ParseError> {
+        self.loop_depth += 1;
+        let body = self.parse_statement();
+        self.loop_depth -= 1;
+        Ok(Box::new(body?))
+    }
+    fn parse_while(&mut self) -> Result<Statement, ParseError> {
+        self.next()?;
+        let test = self.parse_paren_expression()?;
+        let body = self.parse_loop_body()?;
+        Ok(Statement::While(WhileStatement {
+            test,
+            body
+        }))
+    }
+    // the `;` after `do ... while (x)` is optional even on the same line
+    fn parse_do_while(&mut self) -> Result<Statement, ParseError> {
+        self.next()?;
+        let body = self.parse_loop_body()?;
+        self.next_check(TokenMatcher::from(TokenType::WHILE))?;
+        let test = self.parse_paren_expression()?;
+        if self.peek_is(TokenType::SEMICOLON)? {
+            self.next()?;
+        }
+        Ok(Statement::DoWhile(DoWhileStatement {
+            body,
+            test
+        }))
+    }
+    // `for (init; test; update)`, `for (left in right)` or `for (left of right)`.
+    // `in` is not an operator in the init, the first `in` makes it a for-in
+    fn parse_for(&mut self) -> Result<Statement, ParseError> {
+        self.next()?;
+        let is_await = self.in_async && self.peek()?.is_some_and(|t| t.is_contextual(TokenType::AWAIT));
+        if is_await {
+            self.next()?;
+        }
+        self.next_check(TokenMatcher::from(TokenType::LPAREN))?;
+        self.scope_stack.push(Scope::new(ScopeType::ScopeBlock));
+        let statement = self.parse_for_rest(is_await);
+        self.scope_stack.pop();
+        statement
+    }
+    fn parse_for_rest(&mut self, is_await: bool) -> Result<Statement, ParseError> {
+        let init = if self.peek_is(TokenType::SEMICOLON)? {
+            None
+        } else if let Some(kind) = self.declaration_kind()? {
+            let decl = self.with_in(false, |p| p.parse_decl(kind))?;
+            Some(ForInit::VariableDeclaration(decl))
+        } else {
+            Some(self.parse_for_init_expression(is_await)?)
+        };
+        let is_of = self.peek()?.is_some_and(|t| t.is_contextual(TokenType::OF));
+        // `for await` only goes with `of`
+        if is_await && !is_of {
+            let t = self.next()?;
+            return Err(self.error(ParseErrorKind::UnexpectedToken(t.value.into_owned())));
+        }
+        if let Some(left) = init {
+            if self.peek_is(TokenType::IN)? || is_of {
+                self.check_for_left(&left)?;
+                self.next()?;
+                let right = if is_of { self.parse_assignment()? } else { self.parse_expression()? };
+                self.next_check(TokenMatcher::from(TokenType::RPAREN))?;
+                let body = self.parse_loop_body()?;
+                if is_of {
+                    return Ok(Statement::ForOf(ForOfStatement { left, right, body, is_await }));
+                }
+                return Ok(Statement::ForIn(ForInStatement { left, right, body }));
+            }
+            if let ForInit::VariableDeclaration(decl) = &left {
+                self.check_initializers(decl)?;
+            }
+            return self.parse_for_loop(Some(left));
+        }
+        self.parse_for_loop(None)
+    }
+    // an expression before `;`, or the target before `in` or `of`, which may destructure
+    fn parse_for_init_expression(&mut self, is_await: bool) -> Result<ForInit, ParseError> {
+        let starts_async = self.peek()?.is_some_and(|t| t.is_contextual(TokenType::ASYNC));
+        let (first, cover) = self.with_cover(|p| p.with_in(false, |p| p.parse_assignment_cover()))?;
+        let is_of = self.peek()?.is_some_and(|t| t.is_contextual(TokenType::OF));
+        // `for (async of` could also start `for (async of => x;;)`, so a plain `async`
+        // can't be the target of for-of. `for ((async) of x)` and `for await` are fine
+        if is_of && starts_async && !is_await && matches!(first, Expression::Identifier(_)) {
+            return Err(self.error(ParseErrorKind::ForOfAsync));
+        }
+        if is_of || self.peek_is(TokenType::IN)? {
+            return Ok(ForInit::Pattern(self.to_assignment_target(first, cover)?));
+        }
+        if let Some(err) = cover.expression {
+            return Err(err);
+        }
+        let expression = self.with_in(false, |p| p.parse_sequence(first))?;
+        Ok(ForInit::Expression(expression))
+    }
+    fn parse_for_loop(&mut self, init: Option<ForInit>) -> Result<Statement, ParseError> {
+        self.next_check(TokenMatcher::from(TokenType::SEMICOLON))?;
+        let test = if self.peek_is(TokenType::SEMICOLON)? {
+            None
+        } else {
+            Some(self.parse_expression()?)
+        };
+        self.next_check(TokenMatcher::from(TokenType::SEMICOLON))?;
+        let update = if self.peek_is(TokenType::RPAREN)? {
+            None
+        } else {
+            Some(self.parse_expression()?)
+        };
+        self.next_check(TokenMatcher::from(TokenType::RPAREN))?;
+        let body = self.parse_loop_body()?;
+        Ok(Statement::For(ForStatement {
+            init,
+            test,
+            update,
+            body
+        }))
+    }
+    // the left side of for-in and for-of binds or assigns one value each iteration
+    fn check_for_left(&self, left: &ForInit) -> Result<(), ParseError> {
+        match left {
+            ForInit::VariableDeclaration(decl) => {
+                if decl.list.len() != 1 || decl.list[0].init.is_some() {
+                    return Err(self.error(ParseErrorKind::ForInOfInitializer));
+                }
+                Ok(())
+            }
+            ForInit::Expression(e) => self.check_simple_target(e),
+            ForInit::Pattern(_) => Ok(())
+        }
+    }
+    fn parse_switch(&mut self) -> Result<Statement, ParseError> {
+        self.next()?;
+        let discriminant = self.parse_paren_expression()?;
+        self.next_check(TokenMatcher::from(TokenType::LBRACE))?;
+        // all the cases share one scope
+        self.scope_stack.push(Scope::new(ScopeType::ScopeBlock));
+        self.breakable_depth += 1;
+        let cases = self.parse_cases();
+        self.breakable_depth -= 1;
+        self.scope_stack.pop();
+        let cases = cases?;
+        self.next_check(TokenMatcher::from(TokenType::RBRACE))?;
+        Ok(Statement::Switch(SwitchStatement {
+            discriminant,
+            cases
+        }))
+    }
+    fn parse_cases(&mut self) -> Result<Vec<SwitchCase>, ParseError> {
+        let mut cases = vec![];
+        let mut has_default = false;
+        while !self.peek_is(TokenType::RBRACE)? {
+            let t = self.next_check(TokenMatcher::from(vec![TokenType::CASE, TokenType::DEFAULT]))?;
+            let test = if t.category == TokenType::CASE {
+                Some(self.parse_expression()?)
+            } else {
+                if has_default {
+                    return Err(self.error(ParseErrorKind::MultipleDefaults));
+                }
+                has_default = true;
+                None
+            };
+            self.next_check(TokenMatcher::from(TokenType::COLON))?;
+            let mut consequent = vec![];
+            while !matches!(self.peek_category()?, Some(TokenType::CASE | TokenType::DEFAULT | TokenType::RBRACE)) {
+                consequent.push(self.parse_statement_list_item()?);
+            }
+            cases.push(SwitchCase {
+                test,
+                consequent
+            });
+        }
+        Ok(cases)
+    }
+    // the label after `break` or `continue`, only on the same line
+    fn parse_jump_label(&mut self) -> Result<Option<Identifier>, ParseError> {
+        if self.newline_before_next()? || !self.peek_is(TokenType::Identifier)? {
+            return Ok(None);
+        }
+        let t = self.next()?;
+        self.check_identifier(&t)?;
+        Ok(Some(Identifier::new(t.name())))
+    }
+    fn find_label(&self, name: Atom) -> Option<&Label> {
+        self.labels.iter().rev().find(|l| l.name == name)
+    }
+    // `break` needs an enclosing loop or switch, `break l` an enclosing label l
+    fn parse_break(&mut self) -> Result<Statement, ParseError> {
+        self.next()?;
+        let label = self.parse_jump_label()?;
+        match &label {
+            Some(l) => {
+                if self.find_label(l.value).is_none() {
+                    return Err(self.error(ParseErrorKind::UndefinedLabel(l.value.to_string())));
+                }
+            }
+            None => {
+                if self.loop_depth == 0 && self.breakable_depth == 0 {
+                    return Err(self.error(ParseErrorKind::IllegalBreak));
+                }
+            }
+        }
+        self.consume_semicolon()?;
+        Ok(Statement::Break(BreakStatement { label }))
+    }
+    // `continue` needs an enclosing loop, `continue l` a label l on a loop
+    fn parse_continue(&mut self) -> Result<Statement, ParseError> {
+        self.next()?;
+        if self.loop_depth == 0 {
+            return Err(self.error(ParseErrorKind::IllegalContinue));
+        }
+        let label = self.parse_jump_label()?;
+        if let Some(l) = &label {
+            match self.find_label(l.value) {
+                Some(found) if found.is_loop => (),
+                Some(_) => return Err(self.error(ParseErrorKind::IllegalContinue)),
+                None => return Err(self.error(ParseErrorKind::UndefinedLabel(l.value.to_string())))
+            }
+        }
+        self.consume_semicolon()?;
+        Ok(Statement::Continue(ContinueStatement { label }))
+    }
+    // `l: statement`, a label on a loop can be the target of `continue`
+    fn parse_labeled(&mut self) -> Result<Statement, ParseError> {
+        let t = self.next()?;
+        self.check_identifier(&t)?;
+        self.next_check(TokenMatcher::from(TokenType::COLON))?;
+        let name = t.name();
+        if self.find_label(name).is_some() {
+            return Err(self.error(ParseErrorKind::DuplicateLabel(name.to_string())));
+        }
+        // look through more labels, `a: b: while (x)` makes both loop labels
+        let mut n = 0;
+        while self.peek_nth(n)?.is_some_and(|t| t.category == TokenType::Identifier) &&
+            self.peek_nth(n + 1)?.is_some_and(|t| t.category == TokenType::COLON) {
+            n += 2;
+        }
+        let is_loop = self.peek_nth(n)?.is_some_and(|t| {
+            matches!(t.category, TokenType::WHILE | TokenType::DO | TokenType::FOR)
+        });
+        self.labels.push(Label { name, is_loop });
+        let body = self.parse_statement();
+        self.labels.pop();
+        Ok(Statement::Labeled(LabeledStatement {
+            label: Identifier::new(name),
+            body: Box::new(body?)
+        }))
+    }
+    // `return [no LineTerminator here] Expression`, only inside a function
+    fn parse_return(&mut self) -> Result<Statement, ParseError> {
+        self.next()?;
+        if !self.in_function {
+            return Err(self.error(ParseErrorKind::IllegalReturn));
+        }
+        let ends = self.newline_before_next()? ||
+            matches!(self.peek_category()?, Some(TokenType::SEMICOLON | TokenType::RBRACE));
+        let argument = if ends { None } else { Some(self.parse_expression()?) };
+        self.consume_semicolon()?;
+        Ok(Statement::Return(ReturnStatement { argument }))
+    }
+    // unlike return, a line break after throw is an error, not the end of the statement
+    fn parse_throw(&mut self) -> Result<Statement, ParseError> {
+        self.next()?;
+        if self.newline_before_next()? {
+            return Err(self.error(ParseErrorKind::NewlineAfterThrow));
+        }
+        let argument = self.parse_expression()?;
+        self.consume_semicolon()?;
+        Ok(Statement::Throw(ThrowStatement { argument }))
+    }
+    // `try {} catch (e) {}`, `try {} catch {}`, `try {} finally {}` or all three
+    fn parse_try(&mut self) -> Result<Statement, ParseError> {
+        self.next()?;
+        let block = self.parse_block()?;
+        let handler = if self.peek_is(TokenType::CATCH)? {
+            self.next()?;
+            Some(self.parse_catch()?)
+        } else {
+            None
+        };
+        let finalizer = if self.peek_is(TokenType::FINALLY)? {
+            self.next()?;
+            Some(self.parse_block()?)
+        } else {
+            None
+        };
+        if handler.is_none() && finalizer.is_none() {
+            return Err(self.error(ParseErrorKind::MissingCatchOrFinally));
+        }
+        Ok(Statement::Try(TryStatement {
+            block,
+            handler,
+            finalizer
+        }))
+    }
+    // the parameter shares a scope with the block, `catch (e) { let e }` is a redeclaration.
+    // a plain name may still be redeclared with `var`
+    fn parse_catch(&mut self) -> Result<CatchClause, ParseError> {
+        self.scope_stack.push(Scope::new(ScopeType::ScopeBlock));
+        let clause = self.parse_catch_rest();
+        self.scope_stack.pop();
+        clause
+    }
+    fn parse_catch_rest(&mut self) -> Result<CatchClause, ParseError> {
+        let param = if self.peek_is(TokenType::LPAREN)? {
+            self.next()?;
+            let param = self.parse_binding_target()?;
+            match &param {
+                Pattern::Identifier(id) => {
+                    self.check_binding_name(id.value)?;
+                    if let Some(scope) = self.scope_stack.last_mut() {
+                        scope.declare_var(id.value).map_err(|k| self.error(k))?;
+                    }
+                }
+                _ => {
+                    let mut names = vec![];
+                    param.bound_names(&mut names);
+                    self.declare_names(&names, false)?;
+                }
+            }
+            self.next_check(TokenMatcher::from(TokenType::RPAREN))?;
+            Some(param)
+        } else {
+            None
+        };
+        self.next_check(TokenMatcher::from(TokenType::LBRACE))?;
+        let body = self.parse_statement_list()?;
+        self.next_check(TokenMatcher::from(TokenType::RBRACE))?;
+        Ok(CatchClause {
+            param,
+            body: BlockStatement { body }
+        })
+    }
+    fn parse_with(&mut self) -> Result<Statement, ParseError> {
+        self.next()?;
+        if self.strict {
+            return Err(self.error(ParseErrorKind::WithInStrictMode));
+        }
+        let object = self.parse_paren_expression()?;
+        let body = self.parse_statement()?;
+        Ok(Statement::With(WithStatement {
+            object,
+            body: Box::new(body)
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lexer::lexer::Lexer;
+    use crate::ast::statement::*;
+    use crate::ast::expression::*;
+    use crate::ast::function::FunctionBody;
+    use crate::ast::variable::*;
+    use crate::parser::Parser;
+    use crate::parser::error::ParseErrorKind;
+
+    fn parse(source: &str) -> Result<Program, ParseErrorKind> {
+        Parser::new(Lexer::new(source)).parse().map_err(|e| e.kind)
+    }
+
+    fn body(source: &str) -> Vec<Statement> {
+        match parse(source) {
+            Ok(program) => program.body,
+            Err(e) => panic!("{:?}: {}", source, e.message())
+        }
+    }
+
+    // the statements of the function a source is made of
+    fn function_body(source: &str) -> Vec<Statement> {
+        match body(source).remove(0) {
+            Statement::Function(f) => match f.body {
+                FunctionBody::Block(b) => b,
+                FunctionBody::Expression(_) => panic!("{:?} is not a block body", source)
+            },
+            s => panic!("{:?} is {:?}", source, s)
+        }
+    }
+
+    #[test]
+    fn asi_at_newline_brace_and_end() {
+        assert_eq!(body("a\nb").len(), 2);
+        assert_eq!(body("{ a\nb }").len(), 1);
+        assert_eq!(body("a").len(), 1);
+        assert_eq!(body("{ a } b").len(), 2);
+        // the `;` after do-while is optional even on the same line
+        assert_eq!(body("do {} while (a) b").len(), 2);
+        assert!(parse("a b").is_err());
+        assert!(parse("let a = 1 let b = 2").is_err());
+        assert!(parse("{ a b }").is_err());
+    }
+
+    #[test]
+    fn no_asi_when_the_next_line_continues() {
+        // `(` and `[` on the next line continue the expression
+        assert_eq!(body("a = b\n(c)").len(), 1);
+        assert_eq!(body("a\n[b]").len(), 1);
+        assert_eq!(body("a\n+ b").len(), 1);
+    }
+
+    #[test]
+    fn no_asi_in_for_header() {
+        assert!(parse("for (a\nb) {}").is_err());
+        assert!(parse("for (a;\n) {}").is_err());
+    }
+
+    #[test]
+    fn postfix_update_restricted() {
+        let statements = body("a\n++b");
+        assert_eq!(statements.len(), 2);
+        match &statements[1] {
+            Statement::Expression(ExpressionStatement { expression: Expression::Update(u) }) => assert!(u.prefix),
+            s => panic!("{:?}", s)
+        }
+        assert!(parse("a\n++").is_err());
+    }
+
+    #[test]
+    fn return_restricted() {
+        let statements = function_body("function f() { return\na }");
+        assert_eq!(statements.len(), 2);
+        assert!(matches!(statements[0], Statement::Return(ReturnStatement { argument: None })));
+    }
+
+    #[test]
+    fn throw_restricted() {
+        assert!(matches!(parse("throw\na"), Err(ParseErrorKind::NewlineAfterThrow)));
+        assert_eq!(body("throw a").len(), 1);
+    }
+
+    #[test]
+    fn break_and_continue_label_restricted() {
+        let statements = body("l: while (a) { break\nl }");
+        let Statement::Labeled(labeled) = &statements[0] else { panic!() };
+        let Statement::While(w) = &*labeled.body else { panic!() };
+        let Statement::Block(b) = &*w.body else { panic!() };
+        assert_eq!(b.body.len(), 2);
+        assert!(matches!(b.body[0], Statement::Break(BreakStatement { label: None })));
+        assert_eq!(body("l: while (a) { continue\nl }").len(), 1);
+    }
+
+    #[test]
+    fn yield_and_async_restricted() {
+        let statements = function_body("function* g() { yield\na }");
+        assert_eq!(statements.len(), 2);
+        // `async` on its own line is an identifier
+        let statements = body("async\nfunction f() {}");
+        assert_eq!(statements.len(), 2);
+        assert!(matches!(statements[1], Statement::Function(_)));
+    }
+
+    #[test]
+    fn for_await_of() {
+        match function_body("async function f() { for await (const a of b); }").remove(0) {
+            Statement::ForOf(f) => assert!(f.is_await),
+            s => panic!("{:?}", s)
+        }
+        match function_body("async function f() { for (a of b); }").remove(0) {
+            Statement::ForOf(f) => assert!(!f.is_await),
+            s => panic!("{:?}", s)
+        }
+        for source in ["async function f() { for await (a in b); }", "async function f() { for await (;;); }", "function f() { for await (a of b); }", "for await (a of b);"] {
+            assert!(parse(source).is_err(), "{}", source);
+        }
+    }
+
+    #[test]
+    fn for_of_async_lookahead() {
+        assert!(matches!(parse("for (async of x);"), Err(ParseErrorKind::ForOfAsync)));
+        assert!(matches!(parse("async function f() { for (async of x); }"), Err(ParseErrorKind::ForOfAsync)));
+        for source in ["for ((async) of x);", "async function f() { for await (async of x); }", "for (\\u0061sync of x);", "for (async in x);", "for (async of => x;;);", "for (async.a of x);"] {
+            assert!(parse(source).is_ok(), "{}", source);
+        }
+    }
+
+    #[test]
+    fn octal_escape_before_use_strict() {
+        assert!(parse(r#""\07"; 'a'"#).is_ok());
+        for source in [r#""\07"; "use strict""#, r#"function f() { '\8'; 'use strict' }"#, r#"'use strict'; '\00'"#] {
+            assert!(matches!(parse(source), Err(ParseErrorKind::OctalEscapeInStrictMode(_))), "{}", source);
+        }
+        // the error points at the string with the escape
+        let e = Parser::new(Lexer::new(r#"'a'; "\07"; "use strict";"#)).parse().unwrap_err();
+        assert_eq!((e.span.start.column, e.span.end.column), (6, 11));
+    }
+
+    // the declaration a source is made of
+    fn declaration(source: &str) -> VariableDeclaration {
+        match body(source).remove(0) {
+            Statement::VariableDeclaration(d) => d,
+            s => panic!("{:?} is {:?}", source, s)
+        }
+    }
+
+    // the names each declarator binds
+    fn declared_names(decl: &VariableDeclaration) -> Vec<String> {
+        decl.list.iter().map(|v| {
+            let mut names = vec![];
+            v.id.bound_names(&mut names);
+            names.iter().map(|n| n.as_str()).collect::<Vec<_>>().join(" ")
+        }).collect()
+    }
+
+    #[test]
+    fn declarations() {
+        let decl = declaration("var a, b = 1, c;");
+        assert_eq!(decl.kind, VariableKind::Var);
+        assert_eq!(declared_names(&decl), ["a", "b", "c"]);
+        let inits: Vec<_> = decl.list.iter().map(|v| v.init.is_some()).collect();
+        assert_eq!(inits, [false, true, false]);
+        assert_eq!(declaration("let a, b").kind, VariableKind::Let);
+        assert_eq!(declaration("const a = 1, b = 2").kind, VariableKind::Const);
+        // nested destructuring with defaults
+        let decl = declaration("let { a, b: [c, , d = 1, ...e], ...f } = g, [{ h } = {}, i] = j");
+        assert_eq!(declared_names(&decl), ["a c d e f", "h i"]);
+        match &decl.list[1].id {
+            Pattern::Array(a) => assert!(matches!(a.elements[0], Some(Pattern::Assign(_)))),
+            p => panic!("{:?}", p)
+        }
+    }
+
+    #[test]
+    fn declarations_rejected() {
+        for source in ["const a;", "const a = 1, b;", "for (const a; ;) {}"] {
+            assert!(matches!(parse(source), Err(ParseErrorKind::MissingConstInitializer)), "{}", source);
+        }
+        for source in ["let [a];", "var { a };", "let a, [b];"] {
+            assert!(matches!(parse(source), Err(ParseErrorKind::MissingDestructuringInitializer)), "{}", source);
+        }
+        for source in ["let a; let a;", "let a, a;", "const a = 1; var a;", "var a; let a;", "let { a, b: [a] } = c;"] {
+            assert!(matches!(parse(source), Err(ParseErrorKind::AlreadyDeclared(_))), "{}", source);
+        }
+        // var redeclares freely, and a block has its own lexical scope
+        assert!(parse("var a; var a; { let a; }").is_ok());
+        for source in ["let let;", "let let = 1;", "const let = 1;", "let [let] = a;", "let { a: let } = b;"] {
+            assert!(matches!(parse(source), Err(ParseErrorKind::LetInLexicalDeclaration)), "{}", source);
+        }
+        // `let` is still a name for var outside of strict mode
+        assert!(parse("var let = 1;").is_ok());
+    }
+}
@@ -0,0 +1,154 @@
+use std::collections::VecDeque;
+use crate::lexer::lexer::{Lexer, Checkpoint, LexGoal};
+use crate::lexer::token::{Token, TokenType};
+use crate::lexer::error::LexerError;
+
+// tokens read ahead of the parser, each one remembers the lexer state before it
+// so the parser can look ahead any number of tokens and backtrack
+pub struct TokenBuffer<'a> {
+    lexer: Lexer<'a>,
+    buffer: VecDeque<(Checkpoint<'a>, Token<'a>)>,
+    at_end: bool
+}
+
+// the lexer errors are passed through as they are, ParseError boxes them
+#[allow(clippy::result_large_err)]
+impl<'a> TokenBuffer<'a> {
+    pub fn new(lexer: Lexer<'a>) -> Self {
+        Self { lexer, buffer: VecDeque::new(), at_end: false }
+    }
+    // lex one more token into the buffer, false at the end of input
+    fn fill(&mut self) -> Result<bool, LexerError> {
+        if self.at_end {
+            return Ok(false);
+        }
+        let cp = self.lexer.checkpoint();
+        match self.lexer.next() {
+            Some(Ok(t)) => {
+                self.buffer.push_back((cp, t));
+                Ok(true)
+            }
+            Some(Err(e)) => Err(e),
+            None => {
+                self.at_end = true;
+                Ok(false)
+            }
+        }
+    }
+    // the nth token not consumed yet, peek(0) is the next one
+    pub fn peek(&mut self, n: usize) -> Result<Option<&Token<'a>>, LexerError> {
+        while self.buffer.len() <= n {
+            if !self.fill()? {
+                return Ok(None);
+            }
+        }
+        Ok(self.buffer.get(n).map(|(_, t)| t))
+    }
+    pub fn next(&mut self) -> Result<Option<Token<'a>>, LexerError> {
+        if self.buffer.is_empty() && !self.fill()? {
+            return Ok(None);
+        }
+        Ok(self.buffer.pop_front().map(|(_, t)| t))
+    }
+    pub fn line_text(&self, offset: usize) -> &'a str {
+        self.lexer.line_text(offset)
+    }
+    // the position before the next unconsumed token
+    pub fn checkpoint(&self) -> Checkpoint<'a> {
+        match self.buffer.front() {
+            Some((cp, _)) => cp.clone(),
+            None => self.lexer.checkpoint()
+        }
+    }
+    // drop everything read ahead and continue lexing from cp
+    pub fn rewind(&mut self, cp: Checkpoint<'a>) {
+        self.buffer.clear();
+        self.at_end = false;
+        self.lexer.rewind(cp);
+    }
+    // where an expression starts, a `/` or `/=` read as division is a regexp literal instead
+    pub fn rescan_as_regexp(&mut self) -> Result<(), LexerError> {
+        let is_div = match self.peek(0)? {
+            Some(t) => t.category == TokenType::DIV || t.category == TokenType::DIV_ASSIGN,
+            None => false
+        };
+        if is_div {
+            let cp = self.checkpoint();
+            self.rewind(cp);
+            self.lexer.set_goal(LexGoal::InputElementRegExp);
+            let filled = self.fill();
+            self.lexer.set_goal(LexGoal::InputElementDiv);
+            filled?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn buffer(source: &str) -> TokenBuffer<'_> {
+        TokenBuffer::new(Lexer::new(source))
+    }
+
+    fn next_value(tokens: &mut TokenBuffer) -> Option<String> {
+        tokens.next().unwrap().map(|t| t.value.to_string())
+    }
+
+    #[test]
+    fn peek_ahead() {
+        let mut tokens = buffer("a b c");
+        assert_eq!(tokens.peek(2).unwrap().unwrap().value, "c");
+        assert_eq!(tokens.peek(0).unwrap().unwrap().value, "a");
+        assert!(tokens.peek(3).unwrap().is_none());
+        // peeking doesn't consume
+        assert_eq!(next_value(&mut tokens).as_deref(), Some("a"));
+        assert_eq!(tokens.peek(1).unwrap().unwrap().value, "c");
+        assert_eq!(next_value(&mut tokens).as_deref(), Some("b"));
+        assert_eq!(next_value(&mut tokens).as_deref(), Some("c"));
+        assert_eq!(next_value(&mut tokens), None);
+        assert!(tokens.peek(0).unwrap().is_none());
+    }
+
+    #[test]
+    fn rewind_to_checkpoint() {
+        let mut tokens = buffer("a b c");
+        tokens.next().unwrap();
+        let cp = tokens.checkpoint();
+        tokens.next().unwrap();
+        tokens.peek(1).unwrap();
+        tokens.rewind(cp.clone());
+        assert_eq!(next_value(&mut tokens).as_deref(), Some("b"));
+        // a checkpoint taken after reaching the end still rewinds
+        assert_eq!(next_value(&mut tokens).as_deref(), Some("c"));
+        assert_eq!(next_value(&mut tokens), None);
+        tokens.rewind(cp);
+        assert_eq!(next_value(&mut tokens).as_deref(), Some("b"));
+    }
+
+    #[test]
+    fn rescan_after_peek() {
+        let mut tokens = buffer("/a/g; b");
+        // read ahead as division first, like the parser looking for an arrow
+        assert_eq!(tokens.peek(2).unwrap().unwrap().category, TokenType::DIV);
+        let cp = tokens.checkpoint();
+        tokens.rescan_as_regexp().unwrap();
+        let t = tokens.next().unwrap().unwrap();
+        assert_eq!(t.category, TokenType::REGEXP_LITERAL);
+        assert_eq!((&*t.value, t.regex_flags), ("a", Some("g")));
+        // the goal goes back to division after the literal
+        assert_eq!(tokens.peek(0).unwrap().unwrap().category, TokenType::SEMICOLON);
+        assert_eq!(tokens.peek(1).unwrap().unwrap().value, "b");
+        // rewinding before the literal lexes `/` as division again
+        tokens.rewind(cp);
+        assert_eq!(tokens.next().unwrap().unwrap().category, TokenType::DIV);
+        // `/=` starts a regexp too, a non-division token is left alone
+        let mut tokens = buffer("/=/");
+        tokens.rescan_as_regexp().unwrap();
+        assert_eq!(tokens.next().unwrap().unwrap().value, "=");
+        let mut tokens = buffer("a");
+        tokens.rescan_as_regexp().unwrap();
+        assert_eq!(tokens.next().unwrap().unwrap().category, TokenType::Identifier);
+    }
+}